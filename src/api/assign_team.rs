@@ -0,0 +1,50 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::AssignTeamError;
+
+use super::response::*;
+
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub player_name: String,
+    // Index into the teams of the lobby_update
+    pub team: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), AssignTeamError>> for ApiResponse<Response> {
+    fn from(value: Result<(), AssignTeamError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    AssignTeamError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                    AssignTeamError::ClientIsNotTheHost =>
+                        ApiResponse::client_error(&e, "client is not the host"),
+                    AssignTeamError::GameAlreadyStarted =>
+                        ApiResponse::client_error(&e, "game already started"),
+                    AssignTeamError::GameModeIsNotTeams =>
+                        ApiResponse::client_error(&e, "game mode is not Teams"),
+                    AssignTeamError::PlayerDoesNotExist =>
+                        ApiResponse::client_error(&e, "player does not exist"),
+                    AssignTeamError::TeamDoesNotExist =>
+                        ApiResponse::client_error(&e, "team does not exist"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "assign_team"
+    }
+}
@@ -0,0 +1,48 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::BanPlayerError;
+
+use super::response::*;
+
+/***
+ * Removes a player from the game, and keeps their name and address
+ * from joining it again. Addresses are only banned if the server's
+ * ban_addresses setting is on.
+ */
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub player_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), BanPlayerError>> for ApiResponse<Response> {
+    fn from(value: Result<(), BanPlayerError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    BanPlayerError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                    BanPlayerError::ClientIsNotTheHost =>
+                        ApiResponse::client_error(&e, "client is not the host"),
+                    BanPlayerError::PlayerDoesNotExist =>
+                        ApiResponse::client_error(&e, "player does not exist"),
+                    BanPlayerError::CannotBanSelf =>
+                        ApiResponse::client_error(&e, "host cannot ban themselves"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "ban_player"
+    }
+}
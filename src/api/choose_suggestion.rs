@@ -0,0 +1,52 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::ChooseSuggestionError;
+
+use super::response::*;
+
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub round: usize,
+    // Index into the suggestion_choices of the round's drawing_parameters
+    pub choice: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response {
+    pub drawing_suggestion: String,
+}
+
+impl From<Result<String, ChooseSuggestionError>> for ApiResponse<Response> {
+    fn from(value: Result<String, ChooseSuggestionError>) -> Self {
+        match value {
+            Ok(drawing_suggestion) => {
+                ApiResponse::Ok(Response { drawing_suggestion })
+            },
+            Err(e) => {
+                match e {
+                    ChooseSuggestionError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                    ChooseSuggestionError::ChoiceSubmittedForWrongRound =>
+                        ApiResponse::client_error(&e, "choice submitted for wrong round"),
+                    ChooseSuggestionError::DrawingWasAlreadySubmitted =>
+                        ApiResponse::client_error(&e, "drawing was already submitted for this round"),
+                    ChooseSuggestionError::SuggestionAlreadyChosen =>
+                        ApiResponse::client_error(&e, "suggestion was already chosen for this round"),
+                    ChooseSuggestionError::SelectionWindowClosed =>
+                        ApiResponse::client_error(&e, "it's too late to choose a suggestion"),
+                    ChooseSuggestionError::InvalidChoice =>
+                        ApiResponse::client_error(&e, "choice invalid"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "choose_suggestion"
+    }
+}
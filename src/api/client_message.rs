@@ -0,0 +1,121 @@
+use actix_web_actors::ws;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::client_session::ClientSession;
+
+use super::{*, response::{self, UnknownMessageResponse}};
+
+/// Just enough of a message to know what it was meant to be
+#[derive(Deserialize, Debug)]
+pub struct MessageHeader {
+    pub message_name: Option<String>,
+    pub request_id: Option<Value>,
+}
+
+/// A request along with the request_id it was sent with
+#[derive(Deserialize, Debug)]
+pub struct ClientRequest {
+    // Optional value chosen by the client, echoed back in the response
+    pub request_id: Option<Value>,
+    #[serde(flatten)]
+    pub message: ClientMessage,
+}
+
+/***
+ * Every request a client may send, tagged by its message_name. Adding a
+ * request needs a variant here, an arm in forward and a Handler for it
+ * on the GameServer.
+ */
+#[derive(Deserialize, Debug)]
+#[serde(tag = "message_name", rename_all = "snake_case")]
+pub enum ClientMessage {
+    CreateGame(create_game::Request),
+    JoinGame(join_game::Request),
+    RejoinGame(rejoin_game::Request),
+    JoinAsSpectator(join_as_spectator::Request),
+    StartGame(start_game::Request),
+    SetPlayerReady(set_player_ready::Request),
+    ChooseSuggestion(choose_suggestion::Request),
+    SubmitDrawing(submit_drawing::Request),
+    SubmitDescription(submit_description::Request),
+    SubmitVote(submit_vote::Request),
+    SubmitGuesses(submit_guesses::Request),
+    UpdateGameSettings(update_game_settings::Request),
+    PlayAgain(play_again::Request),
+    UploadCustomDeck(upload_custom_deck::Request),
+    AssignTeam(assign_team::Request),
+    GetGameState(get_game_state::Request),
+    KickPlayer(kick_player::Request),
+    BanPlayer(ban_player::Request),
+    TransferHost(transfer_host::Request),
+    ListPublicRooms(list_public_rooms::Request),
+    // Any message_name that isn't listed above
+    #[serde(other)]
+    Unknown,
+}
+
+impl ClientMessage {
+    /***
+     * Parses a request and its request_id from JSON text. On failure,
+     * returns the serialized error response to send back, which carries
+     * the message_name and request_id of the intended request if they
+     * could be read.
+     */
+    pub fn parse(text: &str) -> Result<(Self, Option<Value>), String> {
+        match serde_json::from_str::<ClientRequest>(text) {
+            Ok(ClientRequest { message: ClientMessage::Unknown, .. }) =>
+                Err(error_response(text, response::UNKNOWN_MESSAGE, "unknown message_name".to_string())),
+            Ok(req) => Ok((req.message, req.request_id)),
+            Err(e) =>
+                Err(error_response(text, response::INVALID_REQUEST, format!("invalid request: {}", e))),
+        }
+    }
+
+    pub fn forward(
+        self,
+        request_id: Option<Value>,
+        session: &ClientSession,
+        ctx: &mut ws::WebsocketContext<ClientSession>
+    ) {
+        match self {
+            ClientMessage::CreateGame(req) => session.forward(req, request_id, ctx),
+            ClientMessage::JoinGame(req) => session.forward(req, request_id, ctx),
+            ClientMessage::RejoinGame(req) => session.forward(req, request_id, ctx),
+            ClientMessage::JoinAsSpectator(req) => session.forward(req, request_id, ctx),
+            ClientMessage::StartGame(req) => session.forward(req, request_id, ctx),
+            ClientMessage::SetPlayerReady(req) => session.forward(req, request_id, ctx),
+            ClientMessage::ChooseSuggestion(req) => session.forward(req, request_id, ctx),
+            ClientMessage::SubmitDrawing(req) => session.forward(req, request_id, ctx),
+            ClientMessage::SubmitDescription(req) => session.forward(req, request_id, ctx),
+            ClientMessage::SubmitVote(req) => session.forward(req, request_id, ctx),
+            ClientMessage::SubmitGuesses(req) => session.forward(req, request_id, ctx),
+            ClientMessage::UpdateGameSettings(req) => session.forward(req, request_id, ctx),
+            ClientMessage::PlayAgain(req) => session.forward(req, request_id, ctx),
+            ClientMessage::UploadCustomDeck(req) => session.forward(req, request_id, ctx),
+            ClientMessage::AssignTeam(req) => session.forward(req, request_id, ctx),
+            ClientMessage::GetGameState(req) => session.forward(req, request_id, ctx),
+            ClientMessage::KickPlayer(req) => session.forward(req, request_id, ctx),
+            ClientMessage::BanPlayer(req) => session.forward(req, request_id, ctx),
+            ClientMessage::TransferHost(req) => session.forward(req, request_id, ctx),
+            ClientMessage::ListPublicRooms(req) => session.forward(req, request_id, ctx),
+            // parse never returns it
+            ClientMessage::Unknown => unreachable!("unknown messages are rejected by parse"),
+        }
+    }
+}
+
+/***
+ * Builds the response to a request that couldn't be parsed. Only called
+ * on failure, so reading the header again is fine.
+ */
+fn error_response(text: &str, error_code: &'static str, client_error: String) -> String {
+    let header = serde_json::from_str::<MessageHeader>(text)
+        .unwrap_or(MessageHeader { message_name: None, request_id: None });
+    serde_json::to_string(&UnknownMessageResponse {
+        request_id: header.request_id,
+        message_name: header.message_name,
+        error_code,
+        client_error,
+    }).expect("should be JSON serializable")
+}
@@ -1,5 +1,6 @@
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::game::errors::CreateGameError;
 
@@ -9,21 +10,34 @@ use super::response::*;
 #[rtype(result = "ApiResponse<Response>")]
 pub struct Request {
     pub host_player_name: String,
+    // Needed by everyone else to join. No password if None or empty.
+    #[serde(default)]
+    pub password: Option<String>,
+    // Public rooms are listed by list_public_rooms and GET /rooms
+    #[serde(default)]
+    pub is_public: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Response;
+pub struct Response {
+    // Used with rejoin_game to get back into the game after disconnecting
+    pub reconnect_token: Uuid,
+}
 
-impl From<Result<(), CreateGameError>> for ApiResponse<Response> {
-    fn from(value: Result<(), CreateGameError>) -> Self {
+impl From<Result<Uuid, CreateGameError>> for ApiResponse<Response> {
+    fn from(value: Result<Uuid, CreateGameError>) -> Self {
         match value {
-            Ok(_) => {
-                ApiResponse::Ok(Response)
+            Ok(reconnect_token) => {
+                ApiResponse::Ok(Response{ reconnect_token })
             },
             Err(e) => {
                 match e {
                     CreateGameError::ClientIsAlreadyInAGame =>
-                        ApiResponse::ClientError("client is already in a game".to_string()),
+                        ApiResponse::client_error(&e, "client is already in a game"),
+                    CreateGameError::InvalidPassword =>
+                        ApiResponse::client_error(&e, "password is too long"),
+                    CreateGameError::NoRoomCodeAvailable =>
+                        ApiResponse::client_error(&e, "couldn't find a free room code, try again later"),
                 }
             }
         }
@@ -0,0 +1,38 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::GetGameStateError;
+
+use super::response::*;
+
+/***
+ * Asks for a game_snapshot message, which is sent separately from the response
+ */
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request{}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), GetGameStateError>> for ApiResponse<Response> {
+    fn from(value: Result<(), GetGameStateError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    GetGameStateError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "get_game_state"
+    }
+}
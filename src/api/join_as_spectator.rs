@@ -0,0 +1,44 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::JoinAsSpectatorError;
+
+use super::response::*;
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub room_code: String,
+    // Only needed if the room has one
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), JoinAsSpectatorError>> for ApiResponse<Response> {
+    fn from(value: Result<(), JoinAsSpectatorError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    JoinAsSpectatorError::ClientIsAlreadyInAGame =>
+                        ApiResponse::client_error(&e, "client is already in a game"),
+                    JoinAsSpectatorError::RoomDoesNotExist =>
+                        ApiResponse::client_error(&e, "room does not exist"),
+                    JoinAsSpectatorError::WrongPassword =>
+                        ApiResponse::client_error(&e, "wrong password"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "join_as_spectator"
+    }
+}
\ No newline at end of file
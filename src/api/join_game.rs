@@ -1,5 +1,6 @@
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::game::errors::JoinGameError;
 
@@ -10,27 +11,37 @@ use super::response::*;
 pub struct Request {
     pub room_code: String,
     pub player_name: String,
+    // Only needed if the room has one
+    #[serde(default)]
+    pub password: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Response;
+pub struct Response {
+    // Used with rejoin_game to get back into the game after disconnecting
+    pub reconnect_token: Uuid,
+}
 
-impl From<Result<(), JoinGameError>> for ApiResponse<Response> {
-    fn from(value: Result<(), JoinGameError>) -> Self {
+impl From<Result<Uuid, JoinGameError>> for ApiResponse<Response> {
+    fn from(value: Result<Uuid, JoinGameError>) -> Self {
         match value {
-            Ok(_) => {
-                ApiResponse::Ok(Response)
+            Ok(reconnect_token) => {
+                ApiResponse::Ok(Response{ reconnect_token })
             },
             Err(e) => {
                 match e {
                     JoinGameError::ClientIsAlreadyInAGame =>
-                        ApiResponse::ClientError("client is already in a game".to_string()),
+                        ApiResponse::client_error(&e, "client is already in a game"),
                     JoinGameError::RoomDoesNotExist =>
-                        ApiResponse::ClientError("room does not exist".to_string()),
+                        ApiResponse::client_error(&e, "room does not exist"),
                     JoinGameError::GameFull =>
-                        ApiResponse::ClientError("game is full".to_string()),
+                        ApiResponse::client_error(&e, "game is full"),
                     JoinGameError::GameAlreadyStarted =>
-                        ApiResponse::ClientError("game already started".to_string()),
+                        ApiResponse::client_error(&e, "game already started"),
+                    JoinGameError::WrongPassword =>
+                        ApiResponse::client_error(&e, "wrong password"),
+                    JoinGameError::PlayerIsBanned =>
+                        ApiResponse::client_error(&e, "player is banned from this game"),
                 }
             }
         }
@@ -0,0 +1,46 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::KickPlayerError;
+
+use super::response::*;
+
+/***
+ * Removes a player from the game. They may join again.
+ */
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub player_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), KickPlayerError>> for ApiResponse<Response> {
+    fn from(value: Result<(), KickPlayerError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    KickPlayerError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                    KickPlayerError::ClientIsNotTheHost =>
+                        ApiResponse::client_error(&e, "client is not the host"),
+                    KickPlayerError::PlayerDoesNotExist =>
+                        ApiResponse::client_error(&e, "player does not exist"),
+                    KickPlayerError::CannotKickSelf =>
+                        ApiResponse::client_error(&e, "host cannot kick themselves"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "kick_player"
+    }
+}
@@ -0,0 +1,41 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::game::GameState;
+
+use super::response::*;
+
+/// A public room, as listed for people looking for a game
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoomInfo {
+    pub room_code: String,
+    pub host_name: String,
+    pub player_count: usize,
+    pub max_players: usize,
+    pub spectator_count: usize,
+    pub state: GameState,
+    // Names of the decks the suggestions are drawn from
+    pub decks: Vec<String>,
+    pub has_password: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request{}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response {
+    pub rooms: Vec<RoomInfo>,
+}
+
+impl From<Vec<RoomInfo>> for ApiResponse<Response> {
+    fn from(rooms: Vec<RoomInfo>) -> Self {
+        ApiResponse::Ok(Response { rooms })
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "list_public_rooms"
+    }
+}
@@ -1,12 +1,25 @@
 pub mod create_game;
 pub mod join_game;
+pub mod rejoin_game;
+pub mod join_as_spectator;
 pub mod start_game;
 pub mod set_player_ready;
+pub mod choose_suggestion;
 pub mod submit_drawing;
+pub mod submit_description;
 pub mod submit_vote;
+pub mod submit_guesses;
 pub mod update_game_settings;
 pub mod play_again;
+pub mod upload_custom_deck;
+pub mod assign_team;
+pub mod get_game_state;
+pub mod kick_player;
+pub mod ban_player;
+pub mod transfer_host;
+pub mod list_public_rooms;
 
+pub mod client_message;
 pub mod response;
 
 pub mod server_messages;
\ No newline at end of file
@@ -21,11 +21,11 @@ impl From<Result<(), PlayAgainError>> for ApiResponse<Response> {
             Err(e) => {
                 match e {
                     PlayAgainError::ClientIsNotInAGame =>
-                        ApiResponse::ClientError("client is not in a game".to_string()),
+                        ApiResponse::client_error(&e, "client is not in a game"),
                     PlayAgainError::ClientIsNotTheHost =>
-                        ApiResponse::ClientError("client is not the host".to_string()),
+                        ApiResponse::client_error(&e, "client is not the host"),
                     PlayAgainError::GameIsNotOver =>
-                        ApiResponse::ClientError("game is not over".to_string()),
+                        ApiResponse::client_error(&e, "game is not over"),
                 }
             }
         }
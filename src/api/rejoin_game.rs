@@ -0,0 +1,48 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::game::errors::RejoinGameError;
+
+use super::response::*;
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub room_code: String,
+    // The token from the create_game or join_game response
+    pub reconnect_token: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), RejoinGameError>> for ApiResponse<Response> {
+    fn from(value: Result<(), RejoinGameError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    RejoinGameError::ClientIsAlreadyInAGame =>
+                        ApiResponse::client_error(&e, "client is already in a game"),
+                    RejoinGameError::RoomDoesNotExist =>
+                        ApiResponse::client_error(&e, "room does not exist"),
+                    RejoinGameError::InvalidReconnectToken =>
+                        ApiResponse::client_error(&e, "reconnect token is not valid for this room"),
+                    RejoinGameError::PlayerIsNotDisconnected =>
+                        ApiResponse::client_error(&e, "player is still connected"),
+                    RejoinGameError::PlayerIsBanned =>
+                        ApiResponse::client_error(&e, "player is banned from this game"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "rejoin_game"
+    }
+}
\ No newline at end of file
@@ -1,17 +1,60 @@
 use serde::{Deserialize, Serialize};
 use serde::Serializer;
+use serde_json::Value;
+
+// Error codes for failures that aren't described by the game's error enums
+pub const INVALID_REQUEST: &str = "INVALID_REQUEST";
+pub const UNKNOWN_MESSAGE: &str = "UNKNOWN_MESSAGE";
+pub const SERVER_ERROR: &str = "SERVER_ERROR";
 
 pub trait MessageName{
     fn message_name() -> &'static str;
 }
 
+/// Response to a message that doesn't match any known request
+#[derive(Serialize, Debug)]
+pub struct UnknownMessageResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<Value>,
+    pub message_name: Option<String>,
+    pub error_code: &'static str,
+    pub client_error: String,
+}
+
+/// A response along with the request_id of the request it answers
+#[derive(Serialize, Debug)]
+pub struct ResponseEnvelope<'a, T: MessageName + Serialize> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<Value>,
+    #[serde(flatten)]
+    pub response: &'a ApiResponse<T>,
+}
+
 #[derive(Deserialize, Debug)]
 pub enum ApiResponse<T: MessageName> {
     Ok(T),
-    ClientError(String),
+    // error code, human-readable message
+    ClientError(String, String),
     ServerError(String),
 }
 
+impl<T: MessageName> ApiResponse<T> {
+    /***
+     * Builds a ClientError whose error code is the serialized name of
+     * the error enum variant, e.g. JoinGameError::GameFull => "GAME_FULL".
+     * Variants with fields serialize as a map keyed by the name.
+     */
+    pub fn client_error<E: Serialize>(error: &E, message: &str) -> Self {
+        let error_code = match serde_json::to_value(error) {
+            Ok(Value::String(code)) => code,
+            Ok(Value::Object(map)) if map.len() == 1 =>
+                map.into_iter().next().map(|(code, _)| code).expect("map should have one entry"),
+            _ => INVALID_REQUEST.to_string(),
+        };
+        ApiResponse::ClientError(error_code, message.to_string())
+    }
+}
+
 impl<T: MessageName + Serialize> Serialize for ApiResponse<T>
  {
 
@@ -20,17 +63,19 @@ impl<T: MessageName + Serialize> Serialize for ApiResponse<T>
         S: Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("GenericResponse", 2)?;
+        let mut state = serializer.serialize_struct("GenericResponse", 3)?;
 
         state.serialize_field("message_name", T::message_name())?;
         match self{
             ApiResponse::Ok(x) => {
                 state.serialize_field("success", x)?;
             },
-            ApiResponse::ClientError(v) => {
+            ApiResponse::ClientError(code, v) => {
+                state.serialize_field("error_code", code)?;
                 state.serialize_field("client_error", v)?;
             },
             ApiResponse::ServerError(v) => {
+                state.serialize_field("error_code", SERVER_ERROR)?;
                 state.serialize_field("server_error", v)?;
             },
         }
@@ -0,0 +1,16 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{drawing::Drawing, phase_timer::PhaseDeadline};
+
+/// The drawing a player should describe, in Telephone mode
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "()")]
+pub struct DescribingParameters {
+    pub message_name: String,
+    pub round: usize,
+    // Empty if the previous player didn't finish their drawing
+    pub drawing: Drawing,
+    // None if the describing phase has no time limit
+    pub deadline: Option<PhaseDeadline>,
+}
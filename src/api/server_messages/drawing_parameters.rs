@@ -1,13 +1,22 @@
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::game::drawing::Drawing;
+use crate::game::{drawing::Drawing, phase_timer::PhaseDeadline};
 
 #[derive(Serialize, Deserialize, Debug, Message)]
 #[rtype(result = "()")]
 pub struct DrawingParameters {
     pub message_name: String, //TODO
     pub round: usize,
+    // Coordinates must be within the canvas, from (0, 0) to (canvas_width, canvas_height)
+    pub canvas_width: f32,
+    pub canvas_height: f32,
     pub drawing_suggestion: String,
+    // Every suggestion the player was dealt, including drawing_suggestion
+    pub suggestion_choices: Vec<String>,
+    // When choose_suggestion stops being accepted. None if there's nothing to choose.
+    pub suggestion_deadline: Option<PhaseDeadline>,
     pub imprint: Option<Drawing>,
+    // None if the drawing phase has no time limit
+    pub deadline: Option<PhaseDeadline>,
 }
\ No newline at end of file
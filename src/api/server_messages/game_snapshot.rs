@@ -0,0 +1,44 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{drawing::Drawing, game::GameState, game_settings::GameSettings, phase_timer::PhaseDeadline,
+    player_view::PlayerView, teams::TeamView};
+
+use super::{results::Results, voting_ballot::VotableBallotItem};
+
+/***
+ * Everything a client needs to pick the game up where it is, for a client
+ * that missed some of the other messages. Fields about the current round
+ * are None for spectators and whenever they don't apply to the phase.
+ */
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "()")]
+pub struct GameSnapshot {
+    pub message_name: String,
+    pub room_code: String,
+    pub settings: GameSettings,
+    pub state: GameState,
+    pub round: Option<usize>,
+    pub players: Vec<PlayerView>,
+    pub spectator_count: usize,
+    // Only in Teams mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teams: Option<Vec<TeamView>>,
+    // Deadline of the current phase, if it has a time limit
+    pub deadline: Option<PhaseDeadline>,
+    // Size of the canvas drawings are made on
+    pub canvas_width: f32,
+    pub canvas_height: f32,
+    pub drawing_suggestion: Option<String>,
+    pub suggestion_choices: Option<Vec<String>>,
+    pub suggestion_deadline: Option<PhaseDeadline>,
+    pub imprint: Option<Drawing>,
+    // The drawing the recipient submitted this round
+    pub drawing: Option<Drawing>,
+    // In a Telephone describing phase
+    pub drawing_to_describe: Option<Drawing>,
+    // Only in the voting phase, for players and spectators alike
+    pub ballot: Option<Vec<VotableBallotItem>>,
+    // Only once the game is over
+    pub results: Option<Results>,
+}
@@ -1,7 +1,7 @@
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::game::{game::{GameState}, player_view::PlayerView};
+use crate::game::{game::{GameState}, player_view::PlayerView, teams::TeamView};
 
 #[derive(Serialize, Deserialize, Debug, Message)]
 #[rtype(result = "()")]
@@ -11,4 +11,10 @@ pub struct LobbyUpdate {
     pub state: GameState,
     pub round: Option<usize>,
     pub players: std::vec::Vec<PlayerView>,
+    pub spectator_count: usize,
+    // Only in Teams mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teams: Option<Vec<TeamView>>,
+    pub is_public: bool,
+    pub has_password: bool,
 }
\ No newline at end of file
@@ -1,5 +1,9 @@
 pub mod lobby_update;
 pub mod game_settings_update;
 pub mod drawing_parameters;
+pub mod describing_parameters;
 pub mod voting_ballot;
-pub mod results;
\ No newline at end of file
+pub mod results;
+pub mod game_snapshot;
+pub mod player_removed;
+pub mod room_closed;
\ No newline at end of file
@@ -0,0 +1,26 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalReason {
+    Kicked,
+    Banned,
+    // Disconnected for longer than the grace period
+    Disconnected,
+}
+
+/***
+ * Sent to everyone in a room when a player is taken out of it for good,
+ * including the player if they're still connected
+ */
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "()")]
+pub struct PlayerRemoved {
+    pub message_name: String,
+    pub room_code: String,
+    pub player_name: String,
+    // Whether the recipient is the player who was removed
+    pub is_you: bool,
+    pub reason: RemovalReason,
+}
@@ -1,7 +1,9 @@
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::game::drawing::Drawing;
+use uuid::Uuid;
+
+use crate::game::{drawing::Drawing, telephone::ChainLinkView};
 
 // probably don't implement clone
 #[derive(Serialize, Deserialize, Debug, Message, Clone)]
@@ -12,4 +14,14 @@ pub struct Results {
     pub imprint: Option<Drawing>,
     pub num_votes: i32,
     pub drawing_suggestion: String,
+    pub highest_rated_drawing_id: Option<Uuid>,
+    // Where this game can be found in the history. It's saved in the
+    // background, so it can take a moment to show up.
+    pub game_id: Uuid,
+    // Every chain from start to end, in Telephone mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telephone_chains: Option<Vec<Vec<ChainLinkView>>>,
+    // Names of the teams with the highest score, in Teams mode. More than one if they tied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winning_teams: Option<Vec<String>>,
 }
\ No newline at end of file
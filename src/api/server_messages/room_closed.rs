@@ -0,0 +1,18 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomClosedReason {
+    // Nobody sent a request to the room for too long
+    Idle,
+}
+
+/// Sent to everyone left in a room when the server closes it
+#[derive(Serialize, Deserialize, Debug, Message, Clone)]
+#[rtype(result = "()")]
+pub struct RoomClosed {
+    pub message_name: String,
+    pub room_code: String,
+    pub reason: RoomClosedReason,
+}
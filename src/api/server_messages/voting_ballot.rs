@@ -2,13 +2,15 @@ use actix::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::game::drawing::Drawing;
+use crate::game::{drawing::Drawing, phase_timer::PhaseDeadline};
 
 // TODO: probably don't implement clone
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BallotItem {
     pub id: Uuid,
-    pub suggestion: String,
+    // Hidden in Guessing mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
     // the player's contribution to the drawing
     pub drawing: Drawing,
     // the imprint that the player drew with
@@ -28,4 +30,6 @@ pub struct VotingBallot {
     pub message_name: String, //TODO
     pub round: usize,
     pub ballot: Vec<VotableBallotItem>,
+    // None if the voting phase has no time limit
+    pub deadline: Option<PhaseDeadline>,
 }
\ No newline at end of file
@@ -23,7 +23,7 @@ impl From<Result<(), SetPlayerReadyError>> for ApiResponse<Response> {
             Err(e) => {
                 match e {
                     SetPlayerReadyError::ClientIsNotInAGame =>
-                        ApiResponse::ClientError("client is not in a game".to_string()),
+                        ApiResponse::client_error(&e, "client is not in a game"),
                 }
             }
         }
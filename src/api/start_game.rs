@@ -22,13 +22,17 @@ impl From<Result<(), StartGameError>> for ApiResponse<Response> {
             Err(e) => {
                 match e {
                     StartGameError::ClientIsNotInAGame =>
-                        ApiResponse::ClientError("client is not in a game".to_string()),
+                        ApiResponse::client_error(&e, "client is not in a game"),
                     StartGameError::ClientIsNotTheHost =>
-                        ApiResponse::ClientError("client is not the host".to_string()),
+                        ApiResponse::client_error(&e, "client is not the host"),
                     StartGameError::GameAlreadyStarted =>
-                        ApiResponse::ClientError("game already started".to_string()),
+                        ApiResponse::client_error(&e, "game already started"),
                     StartGameError::MinimumPlayersNotReached =>
-                        ApiResponse::ClientError("not enough players to start game".to_string()),
+                        ApiResponse::client_error(&e, "not enough players to start game"),
+                    StartGameError::NotEnoughDrawingSuggestions =>
+                        ApiResponse::client_error(&e, "included decks don't have any words"),
+                    StartGameError::TeamHasNoPlayers =>
+                        ApiResponse::client_error(&e, "every team needs at least one player"),
                 }
             }
         }
@@ -0,0 +1,45 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::SubmitDescriptionError;
+
+use super::response::*;
+
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub round: usize,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), SubmitDescriptionError>> for ApiResponse<Response> {
+    fn from(value: Result<(), SubmitDescriptionError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    SubmitDescriptionError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                    SubmitDescriptionError::DescriptionSubmittedForWrongRound =>
+                        ApiResponse::client_error(&e, "description submitted for wrong round"),
+                    SubmitDescriptionError::DescriptionWasAlreadySubmitted =>
+                        ApiResponse::client_error(&e, "description was already submitted for this round"),
+                    SubmitDescriptionError::InvalidDescription =>
+                        ApiResponse::client_error(&e, "description must not be empty or too long"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "submit_description"
+    }
+}
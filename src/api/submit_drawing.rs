@@ -24,11 +24,22 @@ impl From<Result<(), SubmitDrawingError>> for ApiResponse<Response> {
             Err(e) => {
                 match e {
                     SubmitDrawingError::ClientIsNotInAGame =>
-                        ApiResponse::ClientError("client is not in a game".to_string()),
+                        ApiResponse::client_error(&e, "client is not in a game"),
                     SubmitDrawingError::DrawingSubmittedForWrongRound =>
-                        ApiResponse::ClientError("drawing submitted for wrong round".to_string()),
+                        ApiResponse::client_error(&e, "drawing submitted for wrong round"),
                     SubmitDrawingError::DrawingWasAlreadySubmitted =>
-                        ApiResponse::ClientError("drawing was already submitted for this round".to_string()),
+                        ApiResponse::client_error(&e, "drawing was already submitted for this round"),
+                    SubmitDrawingError::TooManyStrokes =>
+                        ApiResponse::client_error(&e, "drawing has too many strokes"),
+                    SubmitDrawingError::TooManyPointsInStroke =>
+                        ApiResponse::client_error(&e, "stroke has too many points"),
+                    SubmitDrawingError::CoordinatesOutOfBounds =>
+                        ApiResponse::client_error(&e, "stroke coordinates are outside of the canvas"),
+                    SubmitDrawingError::InvalidBrushSize { min, max } =>
+                        ApiResponse::client_error(&e, &format!("brush_size must be between {} and {}", min, max)),
+                    SubmitDrawingError::InvalidColor =>
+                        ApiResponse::client_error(&e,
+                            "color must be #rgb, #rrggbb, #rrggbbaa, rgb(r, g, b) or rgba(r, g, b, a)"),
                 }
             }
         }
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::game::{errors::SubmitGuessesError, round::GuessResult};
+
+use super::response::*;
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    // Guessed suggestion, by drawing id. Drawings may be left out.
+    pub guesses: HashMap<Uuid, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response {
+    pub results: HashMap<Uuid, GuessResult>,
+}
+
+impl From<Result<HashMap<Uuid, GuessResult>, SubmitGuessesError>> for ApiResponse<Response> {
+    fn from(value: Result<HashMap<Uuid, GuessResult>, SubmitGuessesError>) -> Self {
+        match value {
+            Ok(results) => {
+                ApiResponse::Ok(Response { results })
+            },
+            Err(e) => {
+                match e {
+                    SubmitGuessesError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                    SubmitGuessesError::GameModeIsNotGuessing =>
+                        ApiResponse::client_error(&e, "game mode is not Guessing"),
+                    SubmitGuessesError::NotInGuessingPhase =>
+                        ApiResponse::client_error(&e, "guesses can only be submitted while voting"),
+                    SubmitGuessesError::GuessesAlreadySubmitted =>
+                        ApiResponse::client_error(&e, "guesses were already submitted for this round"),
+                    SubmitGuessesError::ClientGuessedOwnDrawing =>
+                        ApiResponse::client_error(&e, "client cannot guess their own drawing"),
+                    SubmitGuessesError::InvalidDrawingId =>
+                        ApiResponse::client_error(&e, "guesses included an invalid drawing id"),
+                    SubmitGuessesError::GuessTooLong =>
+                        ApiResponse::client_error(&e, "guess is too long"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "submit_guesses"
+    }
+}
@@ -26,15 +26,27 @@ impl From<Result<(), SubmitVoteError>> for ApiResponse<Response> {
             Err(e) => {
                 match e {
                     SubmitVoteError::ClientIsNotInAGame =>
-                        ApiResponse::ClientError("client is not in a game".to_string()),
+                        ApiResponse::client_error(&e, "client is not in a game"),
                     SubmitVoteError::GameHasNotStarted =>
-                        ApiResponse::ClientError("game has not started".to_string()),
+                        ApiResponse::client_error(&e, "game has not started"),
                     SubmitVoteError::MaximumVotesExceeded =>
-                        ApiResponse::ClientError("maximum votes exceeded".to_string()),
+                        ApiResponse::client_error(&e, "maximum votes exceeded"),
                     SubmitVoteError::ClientVotedForSelf =>
-                        ApiResponse::ClientError("client cannot vote for their own drawing".to_string()),
+                        ApiResponse::client_error(&e, "client cannot vote for their own drawing"),
                     SubmitVoteError::InvalidDrawingId =>
-                        ApiResponse::ClientError("votes included an invalid drawing id".to_string())
+                        ApiResponse::client_error(&e, "votes included an invalid drawing id"),
+                    SubmitVoteError::GameModeDoesNotAllowVoting =>
+                        ApiResponse::client_error(&e, "game mode uses guesses instead of votes"),
+                    SubmitVoteError::ClientVotedForTeammate =>
+                        ApiResponse::client_error(&e, "client cannot vote for their teammates' drawings"),
+                    SubmitVoteError::InvalidRanking =>
+                        ApiResponse::client_error(&e, "ranks must count up from 1 without gaps or ties"),
+                    SubmitVoteError::NegativeVotes =>
+                        ApiResponse::client_error(&e, "votes can't be negative"),
+                    SubmitVoteError::NotInVotingPhase =>
+                        ApiResponse::client_error(&e, "votes can only be submitted while voting"),
+                    SubmitVoteError::VoteAlreadySubmitted =>
+                        ApiResponse::client_error(&e, "a vote was already submitted for this round"),
                 }
             }
         }
@@ -0,0 +1,43 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::TransferHostError;
+
+use super::response::*;
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub player_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), TransferHostError>> for ApiResponse<Response> {
+    fn from(value: Result<(), TransferHostError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    TransferHostError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                    TransferHostError::ClientIsNotTheHost =>
+                        ApiResponse::client_error(&e, "client is not the host"),
+                    TransferHostError::PlayerDoesNotExist =>
+                        ApiResponse::client_error(&e, "player does not exist"),
+                    TransferHostError::PlayerIsDisconnected =>
+                        ApiResponse::client_error(&e, "player is disconnected"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "transfer_host"
+    }
+}
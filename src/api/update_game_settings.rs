@@ -24,21 +24,29 @@ impl From<Result<(), UpdateGameSettingsError>> for ApiResponse<Response> {
             Err(e) => {
                 match e {
                     UpdateGameSettingsError::ClientIsNotInAGame =>
-                        ApiResponse::ClientError("client is not in a game".to_string()),
+                        ApiResponse::client_error(&e, "client is not in a game"),
                     UpdateGameSettingsError::ClientIsNotTheHost =>
-                        ApiResponse::ClientError("client is not the host".to_string()),
+                        ApiResponse::client_error(&e, "client is not the host"),
                     UpdateGameSettingsError::GameAlreadyStarted =>
-                        ApiResponse::ClientError("game already started".to_string()),
+                        ApiResponse::client_error(&e, "game already started"),
                     UpdateGameSettingsError::InvalidNumRounds =>
-                        ApiResponse::ClientError("rounds invalid".to_string()),
+                        ApiResponse::client_error(&e, "rounds invalid"),
                     UpdateGameSettingsError::InvalidDrawingTimeLimit =>
-                        ApiResponse::ClientError("drawing_phase_time_limit invalid".to_string()),
+                        ApiResponse::client_error(&e, "drawing_phase_time_limit invalid"),
                     UpdateGameSettingsError::InvalidVotingTimeLimit =>
-                        ApiResponse::ClientError("voting_phase_time_limit invalid".to_string()),
+                        ApiResponse::client_error(&e, "voting_phase_time_limit invalid"),
+                    UpdateGameSettingsError::InvalidDescribingTimeLimit =>
+                        ApiResponse::client_error(&e, "describing_phase_time_limit invalid"),
+                    UpdateGameSettingsError::InvalidNumSuggestionChoices =>
+                        ApiResponse::client_error(&e, "suggestion_choices invalid"),
+                    UpdateGameSettingsError::InvalidNumTeams =>
+                        ApiResponse::client_error(&e, "num_teams invalid"),
+                    UpdateGameSettingsError::InvalidImprintAmount =>
+                        ApiResponse::client_error(&e, "imprint amount invalid"),
                     UpdateGameSettingsError::DeckDoesNotExist =>
-                        ApiResponse::ClientError("deck does not exist".to_string()),
+                        ApiResponse::client_error(&e, "deck does not exist"),
                     UpdateGameSettingsError::SettingRemovesAllDecks =>
-                        ApiResponse::ClientError("cannot have 0 decks".to_string()),
+                        ApiResponse::client_error(&e, "cannot have 0 decks"),
                 }
             }
         }
@@ -0,0 +1,53 @@
+use actix::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::errors::UploadCustomDeckError;
+
+use super::response::*;
+
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+#[rtype(result = "ApiResponse<Response>")]
+pub struct Request {
+    pub deck_name: String,
+    pub words: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response;
+
+impl From<Result<(), UploadCustomDeckError>> for ApiResponse<Response> {
+    fn from(value: Result<(), UploadCustomDeckError>) -> Self {
+        match value {
+            Ok(_) => {
+                ApiResponse::Ok(Response)
+            },
+            Err(e) => {
+                match e {
+                    UploadCustomDeckError::ClientIsNotInAGame =>
+                        ApiResponse::client_error(&e, "client is not in a game"),
+                    UploadCustomDeckError::ClientIsNotTheHost =>
+                        ApiResponse::client_error(&e, "client is not the host"),
+                    UploadCustomDeckError::GameAlreadyStarted =>
+                        ApiResponse::client_error(&e, "game already started"),
+                    UploadCustomDeckError::InvalidDeckName =>
+                        ApiResponse::client_error(&e, "deck_name invalid"),
+                    UploadCustomDeckError::DeckNameIsTaken =>
+                        ApiResponse::client_error(&e, "deck_name is already used by a built-in deck"),
+                    UploadCustomDeckError::TooManyCustomDecks =>
+                        ApiResponse::client_error(&e, "game has too many custom decks"),
+                    UploadCustomDeckError::InvalidNumWords =>
+                        ApiResponse::client_error(&e, "number of words invalid"),
+                    UploadCustomDeckError::InvalidWord =>
+                        ApiResponse::client_error(&e, "words must not be empty, too long or contain control characters"),
+                }
+            }
+        }
+    }
+}
+
+impl MessageName for Response{
+    fn message_name() -> &'static str {
+        "upload_custom_deck"
+    }
+}
@@ -0,0 +1,302 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::game::room_code_generator;
+
+/***
+ * Everything that can be tuned without recompiling. Values come from,
+ * in increasing order of precedence: the defaults below, the TOML config
+ * file, environment variables and command line flags.
+ */
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: String,
+    pub port: u16,
+    // Largest websocket frame accepted from a client, in bytes
+    pub max_frame_size: usize,
+    pub history_dir: PathBuf,
+    // Shortest room code. Codes get longer when most short ones are in use.
+    pub room_code_length: u8,
+    // Letters room codes are drawn from
+    pub room_code_alphabet: String,
+    // Room codes never contain any of these, on top of a built-in list
+    pub room_code_blocklist: Vec<String>,
+    pub cleanup: CleanupConfig,
+    pub game: GameConfig,
+}
+
+/// When abandoned rooms and players are cleaned up
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CleanupConfig {
+    // How often the server looks for rooms and players to clean up
+    pub sweep_interval_seconds: u64,
+    // Rooms that no client has sent a request to for this long are closed
+    pub idle_room_seconds: u64,
+    // Players who disconnected mid-game are removed after this long
+    pub disconnected_player_grace_seconds: u64,
+}
+
+/// Limits that apply to every game on the server
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub decks_dir: PathBuf,
+    pub min_players: usize,
+    pub max_players: usize,
+    pub max_rounds: usize,
+    pub max_phase_time_limit_seconds: u32,
+    pub max_votes_per_round: i32,
+    pub max_suggestion_choices: usize,
+    pub max_teams: usize,
+    // How long players have to choose a suggestion, from the start of the drawing phase
+    pub suggestion_selection_seconds: u32,
+    // Whether banning a player also bans their IP address. Behind a reverse
+    // proxy, or when players share a NAT, that bans everyone on it.
+    pub ban_addresses: bool,
+    pub drawing: DrawingConfig,
+    pub custom_decks: CustomDeckConfig,
+}
+
+/// Limits on submitted drawings
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct DrawingConfig {
+    // Size of the canvas in canvas units. Clients are told in drawing_parameters.
+    pub canvas_width: f32,
+    pub canvas_height: f32,
+    pub min_brush_size: usize,
+    pub max_brush_size: usize,
+    pub max_strokes: usize,
+    pub max_points_per_stroke: usize,
+    // How far (in canvas units) a point may be from the simplified line
+    // before it has to be kept. Simplification is off if unset.
+    pub simplification_tolerance: Option<f32>,
+}
+
+/// Limits on the decks that hosts upload for their own room
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CustomDeckConfig {
+    // Per game
+    pub max_decks: usize,
+    pub max_deck_name_length: usize,
+    pub min_words: usize,
+    pub max_words: usize,
+    pub max_word_length: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind_address: "127.0.0.1".to_string(),
+            port: 8080,
+            max_frame_size: 524_287, // 512KB
+            history_dir: PathBuf::from("./history/"),
+            room_code_length: 4,
+            // No I or O, which are easily mistaken for 1 and 0
+            room_code_alphabet: "ABCDEFGHJKLMNPQRSTUVWXYZ".to_string(),
+            room_code_blocklist: vec![],
+            cleanup: CleanupConfig::default(),
+            game: GameConfig::default(),
+        }
+    }
+}
+
+impl Default for CleanupConfig {
+    fn default() -> Self {
+        CleanupConfig {
+            sweep_interval_seconds: 60,
+            idle_room_seconds: 1800,
+            disconnected_player_grace_seconds: 300,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            decks_dir: PathBuf::from("./decks/"),
+            min_players: 2,
+            max_players: 8,
+            max_rounds: 25,
+            max_phase_time_limit_seconds: 300,
+            max_votes_per_round: 3,
+            max_suggestion_choices: 5,
+            max_teams: 4,
+            suggestion_selection_seconds: 15,
+            ban_addresses: true,
+            drawing: DrawingConfig::default(),
+            custom_decks: CustomDeckConfig::default(),
+        }
+    }
+}
+
+impl Default for DrawingConfig {
+    fn default() -> Self {
+        DrawingConfig {
+            canvas_width: 1000.0,
+            canvas_height: 1000.0,
+            min_brush_size: 1,
+            max_brush_size: 100,
+            max_strokes: 1000,
+            max_points_per_stroke: 2000,
+            simplification_tolerance: Some(0.5),
+        }
+    }
+}
+
+impl Default for CustomDeckConfig {
+    fn default() -> Self {
+        CustomDeckConfig {
+            max_decks: 3,
+            max_deck_name_length: 32,
+            min_words: 1,
+            max_words: 500,
+            max_word_length: 50,
+        }
+    }
+}
+
+/// Command line flags. Each one can also be set with an environment variable.
+#[derive(Parser, Debug)]
+#[command(about = "Drawing game server")]
+pub struct Cli {
+    /// Path to a TOML config file
+    #[arg(long, env = "DRAWING_GAME_CONFIG")]
+    config: Option<PathBuf>,
+    #[arg(long, env = "DRAWING_GAME_BIND_ADDRESS")]
+    bind_address: Option<String>,
+    #[arg(long, env = "DRAWING_GAME_PORT")]
+    port: Option<u16>,
+    #[arg(long, env = "DRAWING_GAME_MAX_FRAME_SIZE")]
+    max_frame_size: Option<usize>,
+    #[arg(long, env = "DRAWING_GAME_HISTORY_DIR")]
+    history_dir: Option<PathBuf>,
+    #[arg(long, env = "DRAWING_GAME_ROOM_CODE_LENGTH")]
+    room_code_length: Option<u8>,
+    #[arg(long, env = "DRAWING_GAME_ROOM_CODE_ALPHABET")]
+    room_code_alphabet: Option<String>,
+    #[arg(long, env = "DRAWING_GAME_DECKS_DIR")]
+    decks_dir: Option<PathBuf>,
+    #[arg(long, env = "DRAWING_GAME_MIN_PLAYERS")]
+    min_players: Option<usize>,
+    #[arg(long, env = "DRAWING_GAME_MAX_PLAYERS")]
+    max_players: Option<usize>,
+    #[arg(long, env = "DRAWING_GAME_MAX_ROUNDS")]
+    max_rounds: Option<usize>,
+    #[arg(long, env = "DRAWING_GAME_MAX_PHASE_TIME_LIMIT_SECONDS")]
+    max_phase_time_limit_seconds: Option<u32>,
+    #[arg(long, env = "DRAWING_GAME_MAX_VOTES_PER_ROUND")]
+    max_votes_per_round: Option<i32>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(&'static str),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "failed to parse {}: {}", path.display(), e),
+            ConfigError::Invalid(reason) => write!(f, "invalid config: {}", reason),
+        }
+    }
+}
+
+impl ServerConfig {
+    /***
+     * Builds the config from the command line, the environment and the
+     * config file they point to, if any
+     */
+    pub fn load() -> Result<Self, ConfigError> {
+        let cli = Cli::parse();
+        let mut config = match &cli.config {
+            Some(path) => Self::from_file(path)?,
+            None => ServerConfig::default(),
+        };
+        config.apply_overrides(cli);
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        toml::from_str(&contents).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    fn apply_overrides(&mut self, cli: Cli) {
+        fn set<T>(field: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        set(&mut self.bind_address, cli.bind_address);
+        set(&mut self.port, cli.port);
+        set(&mut self.max_frame_size, cli.max_frame_size);
+        set(&mut self.history_dir, cli.history_dir);
+        set(&mut self.room_code_length, cli.room_code_length);
+        set(&mut self.room_code_alphabet, cli.room_code_alphabet);
+        set(&mut self.game.decks_dir, cli.decks_dir);
+        set(&mut self.game.min_players, cli.min_players);
+        set(&mut self.game.max_players, cli.max_players);
+        set(&mut self.game.max_rounds, cli.max_rounds);
+        set(&mut self.game.max_phase_time_limit_seconds, cli.max_phase_time_limit_seconds);
+        set(&mut self.game.max_votes_per_round, cli.max_votes_per_round);
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let game = &self.game;
+        if self.room_code_length == 0 {
+            return Err(ConfigError::Invalid("room_code_length must be at least 1"));
+        }
+        if !self.room_code_alphabet.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(ConfigError::Invalid("room_code_alphabet can only have letters and digits"));
+        }
+        if self.room_code_blocklist.iter().any(|s| s.is_empty()) {
+            return Err(ConfigError::Invalid("room_code_blocklist can't have empty entries"));
+        }
+        if room_code_generator::usable_letters(&self.room_code_alphabet, &self.room_code_blocklist).len() < 2 {
+            return Err(ConfigError::Invalid(
+                "room_code_alphabet must have at least 2 different letters or digits that aren't in room_code_blocklist"));
+        }
+        if self.cleanup.sweep_interval_seconds == 0 {
+            return Err(ConfigError::Invalid("sweep_interval_seconds must be at least 1"));
+        }
+        if game.min_players == 0 || game.min_players > game.max_players {
+            return Err(ConfigError::Invalid("min_players must be between 1 and max_players"));
+        }
+        if game.max_rounds == 0 {
+            return Err(ConfigError::Invalid("max_rounds must be at least 1"));
+        }
+        if game.max_votes_per_round < 0 {
+            return Err(ConfigError::Invalid("max_votes_per_round can't be negative"));
+        }
+        if [game.drawing.canvas_width, game.drawing.canvas_height].iter().any(|s| !s.is_finite() || *s <= 0.0) {
+            return Err(ConfigError::Invalid("canvas_width and canvas_height must be positive numbers"));
+        }
+        if game.drawing.min_brush_size > game.drawing.max_brush_size {
+            return Err(ConfigError::Invalid("min_brush_size can't be larger than max_brush_size"));
+        }
+        if game.max_suggestion_choices == 0 {
+            return Err(ConfigError::Invalid("max_suggestion_choices must be at least 1"));
+        }
+        if game.max_teams < 2 {
+            return Err(ConfigError::Invalid("max_teams must be at least 2"));
+        }
+        if game.custom_decks.min_words > game.custom_decks.max_words {
+            return Err(ConfigError::Invalid("custom deck min_words can't be larger than max_words"));
+        }
+        if game.drawing.simplification_tolerance.is_some_and(|t| !t.is_finite() || t < 0.0) {
+            return Err(ConfigError::Invalid("simplification_tolerance must be a positive number"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+/// A drawing suggestion
+#[derive(Debug, Clone)]
+pub struct Card {
+    pub text: String,
+    pub difficulty: Difficulty,
+    // How likely the card is to be drawn relative to the other cards. Never 0.
+    pub weight: u32,
+}
+
+impl Card {
+    pub fn new(text: String, difficulty: Difficulty) -> Self {
+        Card {
+            text,
+            difficulty,
+            weight: 1,
+        }
+    }
+}
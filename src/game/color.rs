@@ -0,0 +1,72 @@
+/// An sRGB color, as parsed from a stroke's color string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color { r: 0, g: 0, b: 0, a: 255 };
+
+    /***
+     * Parses the color formats clients may send:
+     * `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb(r, g, b)` and `rgba(r, g, b, a)`
+     * where a is between 0 and 1
+     */
+    pub fn parse(s: &str) -> Option<Color> {
+        let s = s.trim().to_ascii_lowercase();
+        if let Some(hex) = s.strip_prefix('#') {
+            Self::parse_hex(hex)
+        } else if let Some(args) = s.strip_prefix("rgba(").and_then(|r| r.strip_suffix(')')) {
+            Self::parse_rgb_args(args, true)
+        } else if let Some(args) = s.strip_prefix("rgb(").and_then(|r| r.strip_suffix(')')) {
+            Self::parse_rgb_args(args, false)
+        } else {
+            None
+        }
+    }
+
+    /***
+     * Formats as `#rrggbb`, or `#rrggbbaa` if it's not opaque
+     */
+    pub fn to_hex(self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    fn parse_hex(hex: &str) -> Option<Color> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        match hex.len() {
+            3 => {
+                let short = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+                Some(Color { r: short(0)?, g: short(1)?, b: short(2)?, a: 255 })
+            },
+            6 => Some(Color { r: channel(0)?, g: channel(2)?, b: channel(4)?, a: 255 }),
+            8 => Some(Color { r: channel(0)?, g: channel(2)?, b: channel(4)?, a: channel(6)? }),
+            _ => None,
+        }
+    }
+
+    fn parse_rgb_args(args: &str, has_alpha: bool) -> Option<Color> {
+        let parts: Vec<&str> = args.split(',').map(|p| p.trim()).collect();
+        if parts.len() != if has_alpha { 4 } else { 3 } {
+            return None;
+        }
+        let channel = |p: &str| p.parse::<u8>().ok();
+        let a = if has_alpha {
+            let alpha = parts[3].parse::<f32>().ok().filter(|a| (0.0..=1.0).contains(a))?;
+            (alpha * 255.0).round() as u8
+        } else {
+            255
+        };
+        Some(Color { r: channel(parts[0])?, g: channel(parts[1])?, b: channel(parts[2])?, a })
+    }
+}
@@ -1,40 +1,55 @@
-use std::{fs, io::Read};
+use log::debug;
+use rand::{distributions::{Distribution, WeightedIndex}, Rng};
 
-use rand::seq::SliceRandom;
+use super::card::{Card, Difficulty};
 
+/***
+ * Cards are drawn at random by weight. Drawn cards are discarded and only
+ * come back once every other card has been drawn, so a game doesn't repeat
+ * suggestions unless it has to.
+ */
 #[derive(Debug)]
-pub struct Deck<CardT> {
-    v: Vec<CardT>
+pub struct Deck {
+    draw_pile: Vec<Card>,
+    discard_pile: Vec<Card>,
 }
 
-impl Deck<String> {
-    pub fn from(mut file: fs::File) -> Result<Self, std::io::Error> {
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        let v = serde_json::from_str(&contents)?;
-        Ok(Deck{
-            v: v,
-        })
-    }
-}
-
-impl<CardT> Deck<CardT> {
-    pub fn from_decks(decks: Vec<Self>) -> Self {
+impl Deck {
+    pub fn new(cards: Vec<Card>) -> Self {
         Deck {
-            v: decks.into_iter().map(|d| d.v).flatten().collect(),
+            draw_pile: cards,
+            discard_pile: vec![],
         }
     }
 
-    pub fn add_card(&mut self, card: CardT) {
-        self.v.push(card);
+    pub fn from_decks(decks: Vec<Self>) -> Self {
+        Deck::new(decks.into_iter().flat_map(|d| d.draw_pile.into_iter().chain(d.discard_pile)).collect())
     }
 
-    pub fn draw_card(&mut self) -> Option<CardT> {
-        self.v.pop()
+    pub fn is_empty(&self) -> bool {
+        self.draw_pile.is_empty() && self.discard_pile.is_empty()
     }
 
-    pub fn shuffle(&mut self) {
-        self.v.shuffle(&mut rand::thread_rng());
-    }
+    /***
+     * Draws a card of the preferred difficulty if there's one left, or
+     * any card otherwise. Returns None only if the deck has no cards at all.
+     */
+    pub fn draw_card(&mut self, preferred_difficulty: Option<Difficulty>, rng: &mut impl Rng) -> Option<Card> {
+        if self.draw_pile.is_empty() {
+            debug!("Reshuffling {} discarded cards", self.discard_pile.len());
+            std::mem::swap(&mut self.draw_pile, &mut self.discard_pile);
+        }
 
-}
\ No newline at end of file
+        let preferred: Vec<usize> = self.draw_pile.iter().enumerate()
+            .filter(|(_, card)| Some(card.difficulty) == preferred_difficulty)
+            .map(|(i, _)| i)
+            .collect();
+        let candidates = if preferred.is_empty() { (0..self.draw_pile.len()).collect() } else { preferred };
+
+        let weights = candidates.iter().map(|i| self.draw_pile[*i].weight);
+        let chosen = WeightedIndex::new(weights).ok()?.sample(rng);
+        let card = self.draw_pile.swap_remove(candidates[chosen]);
+        self.discard_pile.push(card.clone());
+        Some(card)
+    }
+}
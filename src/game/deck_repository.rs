@@ -1,17 +1,269 @@
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}, sync::{Arc, RwLock}};
 
-static DECKS: &'static[&'static str] = &[
-    "animals",
-    "clothing",
-    "geo-political",
-    "hobbies",
-    "musical-instruments",
-    "occupations",
-    "space",
-    "transportation",
-    "fruits-and-vegetables",
-    "misc",
-    ];
-
-pub fn get_available_deck_names() -> &'static[&'static str] {
-    DECKS
-}
\ No newline at end of file
+use log::{error, info, warn};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+
+use super::card::{Card, Difficulty};
+
+/// What a deck file looks like on disk
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct DeckFile {
+    display_name: Option<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default = "default_language")]
+    language: String,
+    // Difficulty of the cards that don't set their own
+    #[serde(default)]
+    difficulty: Difficulty,
+    cards: Vec<CardEntry>,
+}
+
+/// A card is either just its text, or its text along with how it's drawn
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum CardEntry {
+    Text(String),
+    Detailed {
+        text: String,
+        difficulty: Option<Difficulty>,
+        weight: Option<u32>,
+    },
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+impl DeckFile {
+    /***
+     * Decks used to be just a list of cards. Those are still read, with
+     * the default metadata.
+     */
+    fn parse(contents: &str) -> serde_json::Result<Self> {
+        if contents.trim_start().starts_with('[') {
+            return Ok(DeckFile {
+                display_name: None,
+                description: String::new(),
+                language: default_language(),
+                difficulty: Difficulty::default(),
+                cards: serde_json::from_str(contents)?,
+            });
+        }
+        serde_json::from_str(contents)
+    }
+}
+
+/// A deck as listed by `GET /decks`
+#[derive(Serialize, Debug, Clone)]
+pub struct DeckInfo {
+    // File name without the extension. This is the name used in game settings.
+    pub name: String,
+    pub display_name: String,
+    pub description: String,
+    pub language: String,
+    pub difficulty: Difficulty,
+    pub card_count: usize,
+}
+
+#[derive(Debug)]
+pub struct LoadedDeck {
+    pub info: DeckInfo,
+    pub cards: Vec<Card>,
+}
+
+#[derive(Debug)]
+pub enum DeckLoadError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, serde_json::Error),
+    NoCards(PathBuf),
+    InvalidCard(PathBuf),
+}
+
+impl std::fmt::Display for DeckLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeckLoadError::Io(path, e) => write!(f, "failed to read deck {}: {}", path.display(), e),
+            DeckLoadError::Parse(path, e) => write!(f, "failed to parse deck {}: {}", path.display(), e),
+            DeckLoadError::NoCards(path) => write!(f, "deck {} has no cards", path.display()),
+            DeckLoadError::InvalidCard(path) =>
+                write!(f, "deck {} has a card that is empty or has a weight of 0", path.display()),
+        }
+    }
+}
+
+/***
+ * The built-in decks: every `.json` file in a directory, parsed once and
+ * kept in memory. Shared between the game server and the HTTP routes.
+ */
+#[derive(Debug)]
+pub struct DeckRepository {
+    dir: PathBuf,
+    decks: RwLock<BTreeMap<String, Arc<LoadedDeck>>>,
+}
+
+impl DeckRepository {
+    /***
+     * Loads every deck in `dir`. Fails with every file that couldn't be
+     * loaded, so they can all be fixed at once.
+     */
+    pub fn load(dir: &Path) -> Result<Self, Vec<DeckLoadError>> {
+        let (decks, errors) = load_dir(dir);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        info!("Loaded {} decks from {}", decks.len(), dir.display());
+        Ok(DeckRepository {
+            dir: dir.to_path_buf(),
+            decks: RwLock::new(decks),
+        })
+    }
+
+    /***
+     * Rereads the directory. Decks whose files became invalid keep their
+     * last good version until they're fixed or removed.
+     */
+    pub fn reload(&self) {
+        let (mut decks, errors) = load_dir(&self.dir);
+        let mut current = self.decks.write().expect("deck lock should not be poisoned");
+        for e in errors {
+            error!("{}", e);
+            let name = match &e {
+                DeckLoadError::Io(path, _) | DeckLoadError::Parse(path, _)
+                | DeckLoadError::NoCards(path) | DeckLoadError::InvalidCard(path) => deck_name(path),
+            };
+            if let Some(deck) = name.and_then(|n| current.get(&n)) {
+                decks.insert(deck.info.name.clone(), deck.clone());
+            }
+        }
+        info!("Reloaded {} decks from {}", decks.len(), self.dir.display());
+        *current = decks;
+    }
+
+    /***
+     * Reloads the decks whenever something in the directory changes, for
+     * as long as the returned watcher is kept alive
+     */
+    pub fn watch(self: &Arc<Self>) -> notify::Result<RecommendedWatcher> {
+        let repository = Arc::downgrade(self);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            match event {
+                Ok(event) if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() => {
+                    if let Some(repository) = repository.upgrade() {
+                        repository.reload();
+                    }
+                },
+                Ok(_) => (),
+                Err(e) => warn!("Error watching decks: {}", e),
+            }
+        })?;
+        watcher.watch(&self.dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<LoadedDeck>> {
+        self.decks.read().expect("deck lock should not be poisoned").get(name).cloned()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.decks.read().expect("deck lock should not be poisoned").contains_key(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.decks.read().expect("deck lock should not be poisoned").keys().cloned().collect()
+    }
+
+    pub fn list(&self) -> Vec<DeckInfo> {
+        self.decks.read().expect("deck lock should not be poisoned").values().map(|d| d.info.clone()).collect()
+    }
+}
+
+fn deck_name(path: &Path) -> Option<String> {
+    path.file_stem().and_then(|s| s.to_str()).map(|s| s.to_string())
+}
+
+fn load_dir(dir: &Path) -> (BTreeMap<String, Arc<LoadedDeck>>, Vec<DeckLoadError>) {
+    let mut decks = BTreeMap::new();
+    let mut errors = vec![];
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => return (decks, vec![DeckLoadError::Io(dir.to_path_buf(), e)]),
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match load_deck(&path) {
+            Ok(deck) => { decks.insert(deck.info.name.clone(), Arc::new(deck)); },
+            Err(e) => errors.push(e),
+        }
+    }
+    (decks, errors)
+}
+
+fn load_deck(path: &Path) -> Result<LoadedDeck, DeckLoadError> {
+    let contents = fs::read_to_string(path).map_err(|e| DeckLoadError::Io(path.to_path_buf(), e))?;
+    let file = DeckFile::parse(&contents).map_err(|e| DeckLoadError::Parse(path.to_path_buf(), e))?;
+    if file.cards.is_empty() {
+        return Err(DeckLoadError::NoCards(path.to_path_buf()));
+    }
+    let cards: Vec<Card> = file.cards.into_iter()
+        .map(|entry| match entry {
+            CardEntry::Text(text) => Card::new(text, file.difficulty),
+            CardEntry::Detailed { text, difficulty, weight } => Card {
+                text,
+                difficulty: difficulty.unwrap_or(file.difficulty),
+                weight: weight.unwrap_or(1),
+            },
+        })
+        .collect();
+    if cards.iter().any(|c| c.text.trim().is_empty() || c.weight == 0) {
+        return Err(DeckLoadError::InvalidCard(path.to_path_buf()));
+    }
+    let name = deck_name(path).ok_or_else(|| DeckLoadError::Io(
+        path.to_path_buf(), std::io::Error::other("file name is not valid UTF-8")))?;
+    Ok(LoadedDeck {
+        info: DeckInfo {
+            display_name: file.display_name.unwrap_or_else(|| name.clone()),
+            name,
+            description: file.description,
+            language: file.language,
+            difficulty: file.difficulty,
+            card_count: cards.len(),
+        },
+        cards,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_decks_with_metadata() {
+        let file = DeckFile::parse(r#"{
+            "display_name": "Animals",
+            "difficulty": "hard",
+            "cards": ["horse", {"text": "bear", "weight": 2}]
+        }"#).unwrap();
+        assert_eq!(file.display_name.as_deref(), Some("Animals"));
+        assert_eq!(file.difficulty, Difficulty::Hard);
+        assert_eq!(file.cards.len(), 2);
+    }
+
+    #[test]
+    fn reads_decks_that_are_just_a_list_of_cards() {
+        let file = DeckFile::parse(r#" ["horse", {"text": "bear", "weight": 2}]"#).unwrap();
+        assert_eq!(file.display_name, None);
+        assert_eq!(file.language, "en");
+        assert_eq!(file.difficulty, Difficulty::default());
+        assert_eq!(file.cards.len(), 2);
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(DeckFile::parse(r#"{"cards": ["horse"], "colour": "red"}"#).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Serialize, Deserialize};
 
-
+// Coordinates are in canvas units, with (0, 0) at the top left. The size
+// of the canvas is set in DrawingConfig.
 pub type Coordinates = std::vec::Vec<(f32, f32)>;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -0,0 +1,118 @@
+use crate::config::DrawingConfig;
+
+use super::{
+    color::Color,
+    drawing::{Coordinates, Drawing, Stroke},
+    errors::SubmitDrawingError};
+
+/***
+ * Checks that a drawing fits on the canvas and within the limits on
+ * strokes, points, brush sizes and colors
+ */
+pub fn validate(drawing: &Drawing, config: &DrawingConfig) -> Result<(), SubmitDrawingError> {
+    if drawing.len() > config.max_strokes {
+        return Err(SubmitDrawingError::TooManyStrokes);
+    }
+    for stroke in drawing {
+        if stroke.coordinates.len() > config.max_points_per_stroke {
+            return Err(SubmitDrawingError::TooManyPointsInStroke);
+        }
+        let on_canvas = |(x, y): &(f32, f32)| {
+            (0.0..=config.canvas_width).contains(x) && (0.0..=config.canvas_height).contains(y)
+        };
+        // NaN and infinity are never in range
+        if !stroke.coordinates.iter().all(on_canvas) {
+            return Err(SubmitDrawingError::CoordinatesOutOfBounds);
+        }
+        if !(config.min_brush_size..=config.max_brush_size).contains(&stroke.brush_size) {
+            return Err(SubmitDrawingError::InvalidBrushSize {
+                min: config.min_brush_size,
+                max: config.max_brush_size,
+            });
+        }
+        if Color::parse(&stroke.color).is_none() {
+            return Err(SubmitDrawingError::InvalidColor);
+        }
+    }
+    Ok(())
+}
+
+/***
+ * Puts a validated drawing in the form that's stored and rebroadcast:
+ * empty strokes are dropped, colors are written as hex and strokes are
+ * simplified
+ */
+pub fn normalize(drawing: Drawing, config: &DrawingConfig) -> Drawing {
+    drawing.into_iter()
+        .filter(|stroke| !stroke.coordinates.is_empty())
+        .map(|stroke| Stroke {
+            coordinates: match config.simplification_tolerance {
+                Some(tolerance) => simplify(&stroke.coordinates, tolerance),
+                None => stroke.coordinates,
+            },
+            brush_size: stroke.brush_size,
+            color: Color::parse(&stroke.color).expect("color should be validated").to_hex(),
+        })
+        .collect()
+}
+
+/***
+ * Ramer–Douglas–Peucker: drops points that are within `tolerance` of the
+ * line through their neighbours. Always keeps the first and last point.
+ */
+pub fn simplify(points: &Coordinates, tolerance: f32) -> Coordinates {
+    if points.len() < 3 {
+        return points.clone();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut segments = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = segments.pop() {
+        let farthest = (start + 1..end)
+            .map(|i| (i, distance_to_segment(points[i], points[start], points[end])))
+            .max_by(|(_, l), (_, r)| l.total_cmp(r));
+        if let Some((i, distance)) = farthest {
+            if distance > tolerance {
+                keep[i] = true;
+                segments.push((start, i));
+                segments.push((i, end));
+            }
+        }
+    }
+    points.iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(point, _)| *point)
+        .collect()
+}
+
+fn distance_to_segment((px, py): (f32, f32), (ax, ay): (f32, f32), (bx, by): (f32, f32)) -> f32 {
+    let (dx, dy) = (bx - ax, by - ay);
+    let length_squared = dx * dx + dy * dy;
+    if length_squared == 0.0 {
+        return ((px - ax).powi(2) + (py - ay).powi(2)).sqrt();
+    }
+    let t = (((px - ax) * dx + (py - ay) * dy) / length_squared).clamp(0.0, 1.0);
+    let (cx, cy) = (ax + t * dx, ay + t * dy);
+    ((px - cx).powi(2) + (py - cy).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_to(x: f32, y: f32) -> Drawing {
+        vec![Stroke { coordinates: vec![(0.0, 0.0), (x, y)], brush_size: 4, color: "#000000".to_string() }]
+    }
+
+    #[test]
+    fn coordinates_must_be_on_the_configured_canvas() {
+        let config = DrawingConfig { canvas_width: 1920.0, canvas_height: 1080.0, ..DrawingConfig::default() };
+        assert!(validate(&line_to(1920.0, 1080.0), &config).is_ok());
+        assert!(matches!(validate(&line_to(1921.0, 500.0), &config), Err(SubmitDrawingError::CoordinatesOutOfBounds)));
+        assert!(matches!(validate(&line_to(500.0, 1081.0), &config), Err(SubmitDrawingError::CoordinatesOutOfBounds)));
+        assert!(matches!(validate(&line_to(-1.0, 0.0), &config), Err(SubmitDrawingError::CoordinatesOutOfBounds)));
+    }
+}
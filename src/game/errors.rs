@@ -1,38 +1,69 @@
+use serde::Serialize;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum JoinGameError{
     ClientIsAlreadyInAGame,
     RoomDoesNotExist,
+    WrongPassword,
     GameFull,
     GameAlreadyStarted,
+    PlayerIsBanned,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JoinAsSpectatorError{
+    ClientIsAlreadyInAGame,
+    RoomDoesNotExist,
+    WrongPassword,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RejoinGameError{
+    ClientIsAlreadyInAGame,
+    RoomDoesNotExist,
+    InvalidReconnectToken,
+    PlayerIsNotDisconnected,
+    PlayerIsBanned,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum StartGameError {
     ClientIsNotInAGame,
     ClientIsNotTheHost,
     GameAlreadyStarted,
     MinimumPlayersNotReached,
+    NotEnoughDrawingSuggestions,
+    TeamHasNoPlayers,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PlayAgainError{
     ClientIsNotInAGame,
     ClientIsNotTheHost,
     GameIsNotOver,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SetPlayerReadyError{
     ClientIsNotInAGame,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum CreateGameError{
     ClientIsAlreadyInAGame,
+    InvalidPassword,
+    NoRoomCodeAvailable,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum UpdateGameSettingsError{
     ClientIsNotInAGame,
     ClientIsNotTheHost,
@@ -40,22 +71,128 @@ pub enum UpdateGameSettingsError{
     InvalidNumRounds,
     InvalidDrawingTimeLimit,
     InvalidVotingTimeLimit,
+    InvalidDescribingTimeLimit,
+    InvalidNumSuggestionChoices,
+    InvalidNumTeams,
+    InvalidImprintAmount,
     DeckDoesNotExist,
     SettingRemovesAllDecks,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SubmitDrawingError{
     ClientIsNotInAGame,
     DrawingSubmittedForWrongRound,
     DrawingWasAlreadySubmitted,
+    TooManyStrokes,
+    TooManyPointsInStroke,
+    CoordinatesOutOfBounds,
+    // The allowed range, so it can be shown to the player
+    InvalidBrushSize { min: usize, max: usize },
+    InvalidColor,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SubmitVoteError{
     ClientIsNotInAGame,
     GameHasNotStarted,
     MaximumVotesExceeded,
     ClientVotedForSelf,
     InvalidDrawingId,
-}
\ No newline at end of file
+    GameModeDoesNotAllowVoting,
+    ClientVotedForTeammate,
+    InvalidRanking,
+    NegativeVotes,
+    NotInVotingPhase,
+    VoteAlreadySubmitted,
+}
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum UploadCustomDeckError{
+    ClientIsNotInAGame,
+    ClientIsNotTheHost,
+    GameAlreadyStarted,
+    InvalidDeckName,
+    DeckNameIsTaken,
+    TooManyCustomDecks,
+    InvalidNumWords,
+    InvalidWord,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChooseSuggestionError{
+    ClientIsNotInAGame,
+    ChoiceSubmittedForWrongRound,
+    DrawingWasAlreadySubmitted,
+    SuggestionAlreadyChosen,
+    SelectionWindowClosed,
+    InvalidChoice,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SubmitGuessesError{
+    ClientIsNotInAGame,
+    GameModeIsNotGuessing,
+    NotInGuessingPhase,
+    GuessesAlreadySubmitted,
+    ClientGuessedOwnDrawing,
+    InvalidDrawingId,
+    GuessTooLong,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SubmitDescriptionError{
+    ClientIsNotInAGame,
+    DescriptionSubmittedForWrongRound,
+    DescriptionWasAlreadySubmitted,
+    InvalidDescription,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AssignTeamError{
+    ClientIsNotInAGame,
+    ClientIsNotTheHost,
+    GameAlreadyStarted,
+    GameModeIsNotTeams,
+    PlayerDoesNotExist,
+    TeamDoesNotExist,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GetGameStateError{
+    ClientIsNotInAGame,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum KickPlayerError{
+    ClientIsNotInAGame,
+    ClientIsNotTheHost,
+    PlayerDoesNotExist,
+    CannotKickSelf,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BanPlayerError{
+    ClientIsNotInAGame,
+    ClientIsNotTheHost,
+    PlayerDoesNotExist,
+    CannotBanSelf,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransferHostError{
+    ClientIsNotInAGame,
+    ClientIsNotTheHost,
+    PlayerDoesNotExist,
+    PlayerIsDisconnected,
+}
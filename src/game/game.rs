@@ -1,30 +1,44 @@
-use std::{rc::Rc, fs::File, collections::HashMap, cell::RefCell};
+use std::{rc::Rc, collections::{HashMap, HashSet}, cell::RefCell, net::IpAddr, sync::Arc, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
 
+use actix_web::web;
 use log::{info, error, debug, warn};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 
-use crate::{client_connection::ClientConnection, api::{
+use crate::{client_connection::ClientConnection, config::GameConfig,
+    history::{game_record::{GameRecord, PlayerRecord, RoundRecord, DrawingRecord}, storage::GameHistoryStorage},
+    api::{
     server_messages::{
         lobby_update::{LobbyUpdate},
         drawing_parameters::DrawingParameters,
-        voting_ballot::{BallotItem, VotingBallot, VotableBallotItem}, game_settings_update::GameSettingsUpdate, results::Results}}};
+        describing_parameters::DescribingParameters,
+        voting_ballot::{BallotItem, VotingBallot, VotableBallotItem}, game_settings_update::GameSettingsUpdate, results::Results,
+        game_snapshot::GameSnapshot, player_removed::{PlayerRemoved, RemovalReason},
+        room_closed::{RoomClosed, RoomClosedReason}},
+    list_public_rooms::RoomInfo}};
 use super::{
-    player_view::{Player, PlayerState}, drawing::{Drawing},
-    round::Round, deck::Deck, imprint_selector,
-    game_settings::{GameSettings, GameMode},
-    deck_repository::{self, get_available_deck_names},
+    player_view::{Player, PlayerState, PlayerView}, drawing::{Drawing}, drawing_validator,
+    round::{Round, GuessResult}, telephone::{self, TelephoneChains}, teams, deck::Deck, card::{Card, Difficulty}, imprint_selector, imprint_mapper, phase_timer::{PhaseTimer, PhaseDeadline},
+    game_settings::{GameSettings, GameMode, DifficultyCurve, ImprintSettings}, scoring::ScoringRuleKind,
+    deck_repository::DeckRepository,
     errors::*};
 
 
-const MIN_PLAYERS: usize = 2;
-const MAX_PLAYERS: usize = 8;
-
+/// Who may find and join a room
+#[derive(Debug, Clone, Default)]
+pub struct RoomPrivacy {
+    pub password: Option<String>,
+    pub is_public: bool,
+}
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameState{
     WaitingForPlayers,
     DrawingPhase,
+    // Only in Telephone mode, between drawing phases
+    DescribingPhase,
     VotingPhase,
     Results,
 }
@@ -32,17 +46,50 @@ pub enum GameState{
 #[derive(Debug)]
 pub struct Game{
     room_code: String,
+    privacy: RoomPrivacy,
     settings: GameSettings,
     state: GameState,
 
     last_player_host_rank: usize,
     host_id: Uuid,
     players: HashMap<Uuid, Rc<RefCell<Player>>>,
+    // Connections watching the game. They receive updates but never play.
+    spectators: HashMap<Uuid, Rc<ClientConnection>>,
+    // Banned players can't join again for as long as the room exists.
+    // Names are trimmed and lowercased. Addresses are only banned if the
+    // config says so.
+    banned_names: HashSet<String>,
+    banned_addrs: HashSet<IpAddr>,
 
     curr_round: Option<usize>, // 1-indexed
     rounds: Vec<Round>,
 
-    drawing_suggestions_deck: Option<Deck<String>>,
+    drawing_suggestions_deck: Option<Deck>,
+    // Everything random in a game is drawn from here, so that a game can be
+    // replayed from its seed. Reseeded every time the game starts. ChaCha8
+    // gives the same numbers for a seed on every platform and rand version,
+    // which StdRng doesn't promise.
+    rng: ChaCha8Rng,
+    seed: u64,
+    // Sent to clients that ask for a snapshot once the game is over
+    results: Option<Results>,
+    // Only in Telephone mode, once the game has started
+    telephone: Option<TelephoneChains>,
+    // Word lists uploaded by the host, by deck name
+    custom_decks: HashMap<String, Vec<String>>,
+
+    // Deadline of the current phase, if it has a time limit
+    phase_timer: Option<PhaseTimer>,
+    // Timers that the server has yet to schedule
+    unscheduled_phase_timers: Vec<PhaseTimer>,
+
+    // Last time a client sent the game a request
+    last_activity: Instant,
+
+    // Where the game is saved once it's over
+    history: Arc<dyn GameHistoryStorage>,
+    config: Rc<GameConfig>,
+    decks: Arc<DeckRepository>,
 }
 
 // Public API
@@ -51,28 +98,54 @@ impl Game{
     pub fn new(
         room_code: String,
         host_player_client_connection: Rc<ClientConnection>,
-        host_player_name: String
+        host_player_name: String,
+        privacy: RoomPrivacy,
+        history: Arc<dyn GameHistoryStorage>,
+        config: Rc<GameConfig>,
+        decks: Arc<DeckRepository>,
     ) -> Self {
         let new_game = Game {
-            room_code: room_code,
+            room_code,
+            privacy,
             settings: GameSettings {
                 mode: GameMode::Default,
-                rounds: 5,
+                rounds: config.max_rounds.min(5),
                 drawing_phase_time_limit_seconds: None,
                 voting_phase_time_limit_seconds: None,
-                drawing_decks_included: deck_repository::get_available_deck_names().into_iter()
-                                            .map(|d| (d.to_string(), true)).collect(),
+                describing_phase_time_limit_seconds: None,
+                drawing_decks_included: decks.names().into_iter()
+                                            .map(|d| (d, true)).collect(),
+                difficulty_curve: DifficultyCurve::Mixed,
+                suggestion_choices: 1,
+                num_teams: 2,
+                scoring_rule: ScoringRuleKind::default(),
+                imprint: ImprintSettings::default(),
+                seed: None,
             },
             state: GameState::WaitingForPlayers,
             last_player_host_rank: 0,
-            host_id: host_player_client_connection.id.clone(),
+            host_id: host_player_client_connection.id,
             players: HashMap::from([(
                 host_player_client_connection.id,
                 Rc::new(RefCell::new(Player::new(host_player_client_connection, host_player_name, 0)))
             )]),
+            spectators: HashMap::new(),
+            banned_names: HashSet::new(),
+            banned_addrs: HashSet::new(),
             curr_round: None,
             rounds: std::vec![],
             drawing_suggestions_deck: None,
+            rng: ChaCha8Rng::seed_from_u64(0),
+            seed: 0,
+            results: None,
+            telephone: None,
+            custom_decks: HashMap::new(),
+            phase_timer: None,
+            unscheduled_phase_timers: std::vec![],
+            last_activity: Instant::now(),
+            history,
+            config,
+            decks,
         };
         new_game.broadcast_lobby_update();
         new_game.broadcast_settings_update();
@@ -83,23 +156,115 @@ impl Game{
         &mut self,
         client_connection: Rc<ClientConnection>,
         proposed_name: &str
-    ) -> Result<(), JoinGameError> {
-        if self.players.len() == MAX_PLAYERS {
+    ) -> Result<Uuid, JoinGameError> {
+        if self.players.len() >= self.config.max_players {
             return Err(JoinGameError::GameFull);
         }
         if self.state != GameState::WaitingForPlayers {
             return Err(JoinGameError::GameAlreadyStarted)
         }
+        if self.is_banned(&client_connection, proposed_name) {
+            return Err(JoinGameError::PlayerIsBanned);
+        }
 
         self.send_settings_update_to_player(&client_connection);
 
         self.last_player_host_rank += 1;
         let player = Player::new(client_connection, self.resolve_name(proposed_name), self.last_player_host_rank);
+        let reconnect_token = player.reconnect_token;
         self.players.insert(player.client.id, Rc::new(RefCell::new(player)));
+        if self.settings.mode == GameMode::Teams {
+            teams::balance(&self.players, self.settings.num_teams);
+        }
 
         info!("CurrentPlayers: {:?}", self.players);
         self.broadcast_lobby_update();
-        return Ok(());
+        Ok(reconnect_token)
+    }
+
+    /***
+     * Spectators may join at any phase, and are caught up on the current
+     * phase if there's something to see
+     */
+    pub fn add_spectator(&mut self, client_connection: Rc<ClientConnection>) {
+        info!("{} is spectating {}", client_connection.id, self.room_code);
+        self.spectators.insert(client_connection.id, client_connection.clone());
+        self.send_settings_update_to_player(&client_connection);
+        if self.state == GameState::VotingPhase {
+            let full_ballot = self.get_full_ballot();
+            self.send_voting_ballots_to_player(&client_connection, &full_ballot);
+        }
+        self.broadcast_lobby_update();
+    }
+
+    pub fn remove_spectator(&mut self, client_id: &Uuid) {
+        if self.spectators.remove(client_id).is_none() {
+            warn!("Spectator {} does not exist in game", client_id);
+        }
+        self.broadcast_lobby_update();
+    }
+
+    /***
+     * Rebinds a disconnected player to a new client connection, and
+     * sends them everything they need to continue the current phase
+     */
+    pub fn rejoin_player(
+        &mut self,
+        client_connection: Rc<ClientConnection>,
+        reconnect_token: &Uuid
+    ) -> Result<(), RejoinGameError> {
+        if self.banned_addrs.contains(&client_connection.peer_addr.ip()) {
+            return Err(RejoinGameError::PlayerIsBanned);
+        }
+        let old_id = self.players.iter()
+            .find(|(_, p)| p.borrow().reconnect_token == *reconnect_token)
+            .map(|(id, _)| *id)
+            .ok_or(RejoinGameError::InvalidReconnectToken)?;
+        let player = self.players.remove(&old_id).expect("player should exist");
+        if !player.borrow().is_disconnected {
+            self.players.insert(old_id, player);
+            return Err(RejoinGameError::PlayerIsNotDisconnected);
+        }
+        info!("{} is rejoining as {}", player.borrow().name, client_connection.id);
+
+        let new_id = client_connection.id;
+        {
+            let mut p = player.borrow_mut();
+            p.client = client_connection.clone();
+            p.is_disconnected = false;
+            p.disconnected_at = None;
+        }
+        self.players.insert(new_id, player);
+        for round in self.rounds.iter_mut() {
+            round.replace_player_id(&old_id, &new_id);
+        }
+        if let Some(telephone) = self.telephone.as_mut() {
+            telephone.replace_player_id(&old_id, &new_id);
+        }
+        self.update_host();
+
+        self.broadcast_lobby_update();
+        self.send_settings_update_to_player(&client_connection);
+        match self.state {
+            GameState::DrawingPhase => self.send_drawing_parameters_to_player(&client_connection),
+            GameState::DescribingPhase => self.send_describing_parameters_to_player(&client_connection),
+            GameState::VotingPhase => {
+                let full_ballot = self.get_full_ballot();
+                self.send_voting_ballots_to_player(&client_connection, &full_ballot);
+            },
+            // The game ended while they were away
+            GameState::Results => {
+                if let Some(results) = &self.results {
+                    self.send_results_to_player(&client_connection, results.clone());
+                }
+            },
+            GameState::WaitingForPlayers => (),
+        }
+        Ok(())
+    }
+
+    pub fn get_reconnect_token(&self, client_id: &Uuid) -> Option<Uuid> {
+        self.players.get(client_id).map(|p| p.borrow().reconnect_token)
     }
 
     /***
@@ -119,16 +284,13 @@ impl Game{
                     warn!("Player {} does not exist in game", client_id);
                 }
             }
-            GameState::DrawingPhase | GameState::VotingPhase => {
+            GameState::DrawingPhase | GameState::DescribingPhase | GameState::VotingPhase => {
                 if let Some(player) = self.players.get_mut(client_id) {
                     player.borrow_mut().is_disconnected = true;
+                    player.borrow_mut().disconnected_at = Some(Instant::now());
                     if !self.all_players_disconnected() {
                         self.update_host();
-                        if self.state == GameState::DrawingPhase {
-                            self.go_to_voting_phase_if_drawing_is_done();
-                        } else if self.state == GameState::VotingPhase {
-                            self.finish_round_if_voting_phase_is_done();
-                        }
+                        self.end_phase_if_done();
                     }
                 } else {
                     warn!("Player {} does not exist in game", client_id);
@@ -140,7 +302,7 @@ impl Game{
 
     pub fn all_players_disconnected(&self) -> bool {
         for (_, player) in self.players.iter() {
-            if player.borrow_mut().is_disconnected == false {
+            if !player.borrow_mut().is_disconnected {
                 return false
             }
         }
@@ -152,25 +314,37 @@ impl Game{
         if self.state != GameState::WaitingForPlayers {
             return Err(UpdateGameSettingsError::GameAlreadyStarted);
         }
-        if !self.is_host(&client_id) {
+        if !self.is_host(client_id) {
             return Err(UpdateGameSettingsError::ClientIsNotTheHost);
         }
 
-        if game_settings.rounds > 25 {
+        if game_settings.rounds == 0 || game_settings.rounds > self.config.max_rounds {
             return Err(UpdateGameSettingsError::InvalidNumRounds);
         }
-        // TODO: need to allow setting time limit when it's implemented
         if let Some(drawing_limit) = game_settings.drawing_phase_time_limit_seconds {
-            if drawing_limit > 300 {
+            if drawing_limit == 0 || drawing_limit > self.config.max_phase_time_limit_seconds {
                 return Err(UpdateGameSettingsError::InvalidDrawingTimeLimit);
             }
-            return Err(UpdateGameSettingsError::InvalidDrawingTimeLimit);
         }
         if let Some(voting_limit) = game_settings.voting_phase_time_limit_seconds {
-            if voting_limit > 300 {
+            if voting_limit == 0 || voting_limit > self.config.max_phase_time_limit_seconds {
                 return Err(UpdateGameSettingsError::InvalidVotingTimeLimit);
             }
-            return Err(UpdateGameSettingsError::InvalidVotingTimeLimit);
+        }
+        if let Some(describing_limit) = game_settings.describing_phase_time_limit_seconds {
+            if describing_limit == 0 || describing_limit > self.config.max_phase_time_limit_seconds {
+                return Err(UpdateGameSettingsError::InvalidDescribingTimeLimit);
+            }
+        }
+        if game_settings.num_teams < 2 || game_settings.num_teams > self.config.max_teams {
+            return Err(UpdateGameSettingsError::InvalidNumTeams);
+        }
+        if !game_settings.imprint.amount.is_valid(self.config.drawing.max_strokes) {
+            return Err(UpdateGameSettingsError::InvalidImprintAmount);
+        }
+        if game_settings.suggestion_choices == 0
+            || game_settings.suggestion_choices > self.config.max_suggestion_choices {
+            return Err(UpdateGameSettingsError::InvalidNumSuggestionChoices);
         }
 
         //verify that this game update doesn't remove all the decks
@@ -184,7 +358,7 @@ impl Game{
         }
 
         for deck in game_settings.drawing_decks_included.keys() {
-            if !get_available_deck_names().contains(&deck.as_str()) {
+            if !self.deck_exists(deck) {
                 return Err(UpdateGameSettingsError::DeckDoesNotExist)
             }
         }
@@ -193,9 +367,23 @@ impl Game{
         self.settings.rounds = game_settings.rounds;
         self.settings.drawing_phase_time_limit_seconds = game_settings.drawing_phase_time_limit_seconds;
         self.settings.voting_phase_time_limit_seconds = game_settings.voting_phase_time_limit_seconds;
+        self.settings.describing_phase_time_limit_seconds = game_settings.describing_phase_time_limit_seconds;
+        self.settings.difficulty_curve = game_settings.difficulty_curve;
+        self.settings.suggestion_choices = game_settings.suggestion_choices;
+        self.settings.num_teams = game_settings.num_teams;
+        self.settings.scoring_rule = game_settings.scoring_rule;
+        self.settings.imprint = game_settings.imprint;
+        self.settings.seed = game_settings.seed;
+        if self.settings.mode == GameMode::Teams {
+            teams::balance(&self.players, self.settings.num_teams);
+        } else {
+            teams::clear(&self.players);
+        }
         for (deck_name, include) in game_settings.drawing_decks_included.iter() {
             self.settings.drawing_decks_included.insert(deck_name.to_string(), *include);
         }
+        // Only once everything is applied, so clients don't see a half-updated lobby
+        self.broadcast_lobby_update();
         self.broadcast_settings_update();
         Ok(())
     }
@@ -207,14 +395,30 @@ impl Game{
         if self.state != GameState::WaitingForPlayers {
             return Err(StartGameError::GameAlreadyStarted);
         }
-        if self.players.len() < MIN_PLAYERS {
+        if self.players.len() < self.config.min_players {
             return Err(StartGameError::MinimumPlayersNotReached);
         }
-        info!("Host is starting the game");
+        let deck = self.init_deck();
+        if deck.is_empty() {
+            return Err(StartGameError::NotEnoughDrawingSuggestions);
+        }
+        if self.settings.mode == GameMode::Teams {
+            teams::balance(&self.players, self.settings.num_teams);
+            let team_views = teams::get_team_views(&self.players, self.settings.num_teams);
+            if team_views.iter().any(|t| t.players.is_empty()) {
+                self.broadcast_lobby_update();
+                return Err(StartGameError::TeamHasNoPlayers);
+            }
+        }
+        self.seed = self.settings.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+        info!("Host is starting game {} with seed {}", self.room_code, self.seed);
 
-        self.drawing_suggestions_deck = Some(self.init_deck());
-        self.start_next_round();
-        Ok(())
+        self.drawing_suggestions_deck = Some(deck);
+        if self.settings.mode == GameMode::Telephone {
+            self.telephone = Some(TelephoneChains::new(self.get_players_in_join_order()));
+        }
+        self.start_next_round()
     }
 
     pub fn play_again(&mut self, client_id: &Uuid) -> Result<(), PlayAgainError> {
@@ -229,6 +433,9 @@ impl Game{
         self.state = GameState::WaitingForPlayers;
         self.curr_round = None;
         self.rounds = std::vec![];
+        self.telephone = None;
+        self.results = None;
+        self.phase_timer = None;
         for p in self.players.values_mut() {
             p.borrow_mut().score = 0;
         }
@@ -236,6 +443,169 @@ impl Game{
         Ok(())
     }
 
+    pub fn is_password_correct(&self, password: Option<&str>) -> bool {
+        self.privacy.password.as_deref().is_none_or(|p| Some(p) == password)
+    }
+
+    /***
+     * How the room is listed for people looking for a game. None if it's private.
+     */
+    pub fn get_room_info(&self) -> Option<RoomInfo> {
+        if !self.privacy.is_public {
+            return None;
+        }
+        let mut decks: Vec<String> = self.settings.drawing_decks_included.iter()
+            .filter(|(_, included)| **included)
+            .map(|(name, _)| name.clone())
+            .collect();
+        decks.sort();
+        Some(RoomInfo {
+            room_code: self.room_code.clone(),
+            host_name: self.players.get(&self.host_id).map(|p| p.borrow().name.clone()).unwrap_or_default(),
+            player_count: self.players.values().filter(|p| !p.borrow().is_disconnected).count(),
+            max_players: self.config.max_players,
+            spectator_count: self.spectators.len(),
+            state: self.state.clone(),
+            decks,
+            has_password: self.privacy.password.is_some(),
+        })
+    }
+
+    /***
+     * Removes a player. They're told why before they're removed, and may join again.
+     * Returns the client id of the removed player.
+     */
+    pub fn kick_player(&mut self, client_id: &Uuid, player_name: &str) -> Result<Uuid, KickPlayerError> {
+        if !self.is_host(client_id) {
+            return Err(KickPlayerError::ClientIsNotTheHost);
+        }
+        let player_id = self.find_player_id(player_name).ok_or(KickPlayerError::PlayerDoesNotExist)?;
+        if player_id == *client_id {
+            return Err(KickPlayerError::CannotKickSelf);
+        }
+        self.remove_player(&player_id, RemovalReason::Kicked);
+        Ok(player_id)
+    }
+
+    /***
+     * Removes a player and keeps their name, and their address if
+     * ban_addresses is set, from joining again.
+     * Returns the client id of the removed player.
+     */
+    pub fn ban_player(&mut self, client_id: &Uuid, player_name: &str) -> Result<Uuid, BanPlayerError> {
+        if !self.is_host(client_id) {
+            return Err(BanPlayerError::ClientIsNotTheHost);
+        }
+        let player_id = self.find_player_id(player_name).ok_or(BanPlayerError::PlayerDoesNotExist)?;
+        if player_id == *client_id {
+            return Err(BanPlayerError::CannotBanSelf);
+        }
+        {
+            let player = self.players[&player_id].borrow();
+            self.banned_names.insert(player.name.trim().to_lowercase());
+            if self.config.ban_addresses {
+                self.banned_addrs.insert(player.client.peer_addr.ip());
+            }
+        }
+        self.remove_player(&player_id, RemovalReason::Banned);
+        Ok(player_id)
+    }
+
+    /***
+     * Makes another connected player the host. They take over the host's
+     * place in line, so the host doesn't change back when someone leaves.
+     */
+    pub fn transfer_host(&mut self, client_id: &Uuid, player_name: &str) -> Result<(), TransferHostError> {
+        if !self.is_host(client_id) {
+            return Err(TransferHostError::ClientIsNotTheHost);
+        }
+        let player_id = self.find_player_id(player_name).ok_or(TransferHostError::PlayerDoesNotExist)?;
+        let new_host = self.players[&player_id].clone();
+        if new_host.borrow().is_disconnected {
+            return Err(TransferHostError::PlayerIsDisconnected);
+        }
+        let old_host = self.players[client_id].clone();
+        if !Rc::ptr_eq(&old_host, &new_host) {
+            std::mem::swap(&mut old_host.borrow_mut().host_rank, &mut new_host.borrow_mut().host_rank);
+        }
+        self.host_id = player_id;
+        self.broadcast_lobby_update();
+        Ok(())
+    }
+
+    /***
+     * Moves a player to another team. Players are assigned a team as they
+     * join, so this is only needed to change the default.
+     */
+    pub fn assign_team(&mut self, client_id: &Uuid, player_name: &str, team: usize)
+    -> Result<(), AssignTeamError> {
+        if !self.is_host(client_id) {
+            return Err(AssignTeamError::ClientIsNotTheHost);
+        }
+        if self.state != GameState::WaitingForPlayers {
+            return Err(AssignTeamError::GameAlreadyStarted);
+        }
+        if self.settings.mode != GameMode::Teams {
+            return Err(AssignTeamError::GameModeIsNotTeams);
+        }
+        if team >= self.settings.num_teams {
+            return Err(AssignTeamError::TeamDoesNotExist);
+        }
+        let player_id = self.find_player_id(player_name).ok_or(AssignTeamError::PlayerDoesNotExist)?;
+        self.players[&player_id].borrow_mut().team = Some(team);
+        self.broadcast_lobby_update();
+        Ok(())
+    }
+
+    /***
+     * Adds a deck of the host's own words to the game, or replaces the
+     * custom deck with the same name. New decks are included by default.
+     */
+    pub fn upload_custom_deck(&mut self, client_id: &Uuid, deck_name: String, words: Vec<String>)
+    -> Result<(), UploadCustomDeckError> {
+        if !self.is_host(client_id) {
+            return Err(UploadCustomDeckError::ClientIsNotTheHost);
+        }
+        if self.state != GameState::WaitingForPlayers {
+            return Err(UploadCustomDeckError::GameAlreadyStarted);
+        }
+        let limits = &self.config.custom_decks;
+
+        let deck_name = deck_name.trim().to_string();
+        if deck_name.is_empty()
+            || deck_name.chars().count() > limits.max_deck_name_length
+            || deck_name.chars().any(char::is_control) {
+            return Err(UploadCustomDeckError::InvalidDeckName);
+        }
+        if self.decks.contains(&deck_name) {
+            return Err(UploadCustomDeckError::DeckNameIsTaken);
+        }
+        if !self.custom_decks.contains_key(&deck_name) && self.custom_decks.len() >= limits.max_decks {
+            return Err(UploadCustomDeckError::TooManyCustomDecks);
+        }
+
+        let mut deduped_words: Vec<String> = vec![];
+        for word in words.iter().map(|w| w.trim()) {
+            if word.is_empty()
+                || word.chars().count() > limits.max_word_length
+                || word.chars().any(char::is_control) {
+                return Err(UploadCustomDeckError::InvalidWord);
+            }
+            if !deduped_words.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+                deduped_words.push(word.to_string());
+            }
+        }
+        if deduped_words.len() < limits.min_words || deduped_words.len() > limits.max_words {
+            return Err(UploadCustomDeckError::InvalidNumWords);
+        }
+
+        info!("Host uploaded custom deck {} with {} words", deck_name, deduped_words.len());
+        self.settings.drawing_decks_included.insert(deck_name.clone(), true);
+        self.custom_decks.insert(deck_name, deduped_words);
+        self.broadcast_settings_update();
+        Ok(())
+    }
+
     pub fn set_player_ready(&mut self, client_id: &Uuid, ready_state: bool) {
         let player = self.players.get_mut(client_id).expect("player should exist");
         let state = match ready_state { true => PlayerState::Ready, false => PlayerState::NotReady };
@@ -246,46 +616,224 @@ impl Game{
     pub fn submit_drawing(&mut self, client_id: &Uuid, drawing: Drawing, round: usize)
     -> Result<(), SubmitDrawingError> {
         if self.curr_round != Some(round) {
-            error!("Not Current Round: curr_round: {}, round {}", self.curr_round.unwrap(), round);
+            error!("Not Current Round: curr_round: {:?}, round {}", self.curr_round, round);
             return Err(SubmitDrawingError::DrawingSubmittedForWrongRound);
         }
+        if self.state != GameState::DrawingPhase {
+            return Err(SubmitDrawingError::DrawingSubmittedForWrongRound);
+        }
+        drawing_validator::validate(&drawing, &self.config.drawing)?;
+        let drawing = drawing_validator::normalize(drawing, &self.config.drawing);
 
         {
             let round = self.get_current_round_mut().ok_or(SubmitDrawingError::DrawingSubmittedForWrongRound)?;
-            if let Some(_) = round.get_drawing(&client_id) {
+            if round.get_drawing(client_id).is_some() {
                 error!("Drawing already Exists");
                 return Err(SubmitDrawingError::DrawingWasAlreadySubmitted)
             }
 
-            round.set_drawing(&client_id, Rc::new(drawing));
+            round.set_drawing(client_id, Rc::new(drawing));
         }
 
-        self.set_player_state(&client_id, PlayerState::DrawingDone);
-        self.go_to_voting_phase_if_drawing_is_done();
+        self.set_player_state(client_id, PlayerState::DrawingDone);
+        self.end_drawing_phase_if_done();
         Ok(())
     }
 
+    pub fn submit_description(&mut self, client_id: &Uuid, round: usize, description: &str)
+    -> Result<(), SubmitDescriptionError> {
+        if self.state != GameState::DescribingPhase || self.curr_round != Some(round) {
+            return Err(SubmitDescriptionError::DescriptionSubmittedForWrongRound);
+        }
+        let description = description.trim();
+        if description.is_empty()
+            || description.chars().count() > telephone::MAX_DESCRIPTION_LENGTH
+            || description.chars().any(char::is_control) {
+            return Err(SubmitDescriptionError::InvalidDescription);
+        }
+        {
+            let telephone = self.telephone.as_mut().expect("telephone chains should exist in a describing phase");
+            if telephone.has_described(client_id, round) {
+                return Err(SubmitDescriptionError::DescriptionWasAlreadySubmitted);
+            }
+            telephone.add_description(client_id, round, description.to_string());
+        }
+        self.set_player_state(client_id, PlayerState::DescribingDone);
+        self.end_describing_phase_if_done();
+        Ok(())
+    }
+
+    pub fn choose_suggestion(&mut self, client_id: &Uuid, round: usize, choice: usize)
+    -> Result<String, ChooseSuggestionError> {
+        if self.state != GameState::DrawingPhase || self.curr_round != Some(round) {
+            return Err(ChooseSuggestionError::ChoiceSubmittedForWrongRound);
+        }
+        let round = self.get_current_round_mut().ok_or(ChooseSuggestionError::ChoiceSubmittedForWrongRound)?;
+        round.choose_suggestion(client_id, choice)
+    }
+
     pub fn submit_vote(&mut self, client_id: &Uuid, votes: HashMap<Uuid, i32>)
     -> Result<(), SubmitVoteError>{
+        if matches!(self.settings.mode, GameMode::Guessing | GameMode::Telephone) {
+            return Err(SubmitVoteError::GameModeDoesNotAllowVoting);
+        }
+        if self.state != GameState::VotingPhase {
+            return Err(SubmitVoteError::NotInVotingPhase);
+        }
         {
+            let scoring_rule = self.settings.scoring_rule.rule();
             let round = self.get_current_round_mut().ok_or(SubmitVoteError::GameHasNotStarted)?;
-            round.submit_vote(&client_id, votes)?;
+            round.submit_vote(client_id, votes, scoring_rule.as_ref())?;
         }
-        self.set_player_state(&client_id, PlayerState::VotingDone);
+        self.set_player_state(client_id, PlayerState::VotingDone);
         self.finish_round_if_voting_phase_is_done();
         Ok(())
     }
 
+    pub fn submit_guesses(&mut self, client_id: &Uuid, guesses: HashMap<Uuid, String>)
+    -> Result<HashMap<Uuid, GuessResult>, SubmitGuessesError> {
+        if self.settings.mode != GameMode::Guessing {
+            return Err(SubmitGuessesError::GameModeIsNotGuessing);
+        }
+        if self.state != GameState::VotingPhase {
+            return Err(SubmitGuessesError::NotInGuessingPhase);
+        }
+        let results = {
+            let round = self.get_current_round_mut().ok_or(SubmitGuessesError::NotInGuessingPhase)?;
+            round.submit_guesses(client_id, guesses)?
+        };
+        self.set_player_state(client_id, PlayerState::VotingDone);
+        self.finish_round_if_voting_phase_is_done();
+        Ok(results)
+    }
+
+    /***
+     * Hands over the timers started since the last call, so that the
+     * server can schedule them
+     */
+    pub fn take_unscheduled_phase_timers(&mut self) -> Vec<PhaseTimer> {
+        std::mem::take(&mut self.unscheduled_phase_timers)
+    }
 
+    /***
+     * Ends the current phase early if the timer is still the active one.
+     * Timers from phases that already ended are ignored.
+     */
+    pub fn expire_phase_timer(&mut self, timer_id: &Uuid) {
+        if self.phase_timer.as_ref().map(|t| t.id) != Some(*timer_id) {
+            debug!("Ignoring stale phase timer {}", timer_id);
+            return;
+        }
+        info!("Phase timer expired for {:?}", self.state);
+        match self.state {
+            GameState::DrawingPhase => self.end_drawing_phase(),
+            GameState::DescribingPhase => self.end_describing_phase(),
+            GameState::VotingPhase => self.finish_round(),
+            GameState::WaitingForPlayers | GameState::Results => {
+                warn!("Phase timer expired outside of a timed phase");
+            }
+        }
+    }
 }
 
 impl Game{
+    fn find_player_id(&self, player_name: &str) -> Option<Uuid> {
+        self.players.iter()
+            .find(|(_, p)| p.borrow().name == player_name)
+            .map(|(id, _)| *id)
+    }
+
+    fn is_banned(&self, client_connection: &ClientConnection, proposed_name: &str) -> bool {
+        self.banned_addrs.contains(&client_connection.peer_addr.ip())
+            || self.banned_names.contains(&proposed_name.trim().to_lowercase())
+    }
+
+    /***
+     * Takes a player out of the game for good and tells everyone in it.
+     * The current round keeps their drawing if they submitted one, but
+     * stops waiting on them.
+     */
+    fn remove_player(&mut self, player_id: &Uuid, reason: RemovalReason) {
+        let player = self.players.remove(player_id).expect("player should exist");
+        let player_name = player.borrow().name.clone();
+        info!("Removing {} from {} ({:?})", player_name, self.room_code, reason);
+        let removed_client = player.borrow().client.clone();
+        for client in self.get_all_clients().into_iter().chain(std::iter::once(removed_client)) {
+            client.actor_addr.do_send(PlayerRemoved {
+                message_name: "player_removed".to_string(),
+                room_code: self.room_code.clone(),
+                player_name: player_name.clone(),
+                is_you: client.id == *player_id,
+                reason,
+            });
+        }
+        // Telephone chains go on with a gap where the player would have been
+        player.borrow_mut().is_disconnected = true;
+        if let Some(round) = self.get_current_round_mut() {
+            round.remove_player(player_id);
+        }
+        self.end_phase_if_done();
+        self.broadcast_lobby_update();
+    }
+
+    /***
+     * Removes players who have been disconnected for longer than the grace
+     * period. They can no longer rejoin.
+     */
+    pub fn evict_disconnected_players(&mut self, grace_period: Duration) {
+        let evicted: Vec<Uuid> = self.players.iter()
+            .filter(|(_, p)| p.borrow().disconnected_at.is_some_and(|t| t.elapsed() >= grace_period))
+            .map(|(id, _)| *id)
+            .collect();
+        for player_id in evicted {
+            self.remove_player(&player_id, RemovalReason::Disconnected);
+        }
+    }
+
+    /***
+     * Records that a client used the game, which keeps it from being closed as idle
+     */
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    pub fn get_last_activity(&self) -> Instant {
+        self.last_activity
+    }
+
+    /***
+     * Tells everyone still in the game that it's closing. The game manager
+     * drops the game afterwards.
+     */
+    pub fn close(&self, reason: RoomClosedReason) {
+        info!("Closing room {} ({:?})", self.room_code, reason);
+        for client in self.get_all_clients() {
+            client.actor_addr.do_send(RoomClosed {
+                message_name: "room_closed".to_string(),
+                room_code: self.room_code.clone(),
+                reason,
+            });
+        }
+    }
+
+    /***
+     * Moves on if the current phase was only waiting on players who left
+     */
+    fn end_phase_if_done(&mut self) {
+        match self.state {
+            GameState::DrawingPhase => self.end_drawing_phase_if_done(),
+            GameState::DescribingPhase => self.end_describing_phase_if_done(),
+            GameState::VotingPhase => self.finish_round_if_voting_phase_is_done(),
+            GameState::WaitingForPlayers | GameState::Results => {},
+        }
+    }
+
     fn is_host(&self, client_id: &Uuid) -> bool {
         *client_id == self.host_id
     }
 
     fn update_host(&mut self) {
-        self.host_id = self.get_player_with_highest_host_rank().clone();
+        self.host_id = self.get_player_with_highest_host_rank();
     }
 
     fn get_player_with_highest_host_rank(&self) -> Uuid {
@@ -311,6 +859,20 @@ impl Game{
         best_name
     }
 
+    /***
+     * Every connection that should receive game updates, players first
+     */
+    fn get_all_clients(&self) -> Vec<Rc<ClientConnection>> {
+        self.players.values()
+            .map(|p| p.borrow().client.clone())
+            .chain(self.spectators.values().cloned())
+            .collect()
+    }
+
+    fn deck_exists(&self, deck_name: &str) -> bool {
+        self.decks.contains(deck_name) || self.custom_decks.contains_key(deck_name)
+    }
+
     fn get_current_round_mut(&mut self) -> Option<&mut Round> {
         self.rounds.last_mut()
     }
@@ -325,87 +887,282 @@ impl Game{
         }
     }
 
-    fn init_deck(&self) -> Deck<String> {
-        let decks: Vec<_> = self.settings.drawing_decks_included.iter()
+    /***
+     * Player ids ordered by when they joined. Anything random that's done per
+     * player goes in this order, since map order differs between runs.
+     */
+    fn get_players_in_join_order(&self) -> Vec<Uuid> {
+        let mut player_order: Vec<_> = self.players.iter()
+            .map(|(id, p)| (*id, p.borrow().host_rank))
+            .collect();
+        player_order.sort_by_key(|(_, host_rank)| *host_rank);
+        player_order.into_iter().map(|(id, _)| id).collect()
+    }
+
+    fn init_deck(&self) -> Deck {
+        let mut included: Vec<_> = self.settings.drawing_decks_included.iter()
             .filter(|(_, i)| **i)
-            .map(|(n, _)| {
-                Deck::from(File::open(format!("./decks/{}.json", n)).expect("file")).expect("expect")
+            .collect();
+        included.sort();
+        let decks: Vec<_> = included.into_iter()
+            .filter_map(|(n, _)| match self.custom_decks.get(n) {
+                Some(words) => Some(Deck::new(
+                    words.iter().map(|w| Card::new(w.clone(), Difficulty::Medium)).collect())),
+                None => match self.decks.get(n) {
+                    Some(deck) => Some(Deck::new(deck.cards.clone())),
+                    None => {
+                        warn!("Deck {} was removed after it was included", n);
+                        None
+                    }
+                },
             })
             .collect();
-        let mut combined_deck = Deck::from_decks(decks);
-        combined_deck.add_card("rabbit".to_string());
-        combined_deck.shuffle();
-        combined_deck
+        Deck::from_decks(decks)
     }
 
-    fn start_next_round(&mut self) {
+    /***
+     * Deals the next round's suggestions and starts its drawing phase.
+     * Nothing changes if the deck has no cards to deal.
+     */
+    fn start_next_round(&mut self) -> Result<(), StartGameError> {
         let mut imprint_map: HashMap<Uuid, Option<Rc<Drawing>>> = HashMap::new();
-        if let Some(round) = self.get_current_round() {
-            imprint_map = round.get_data().iter()
+        let imprint = self.settings.imprint;
+        let selector = imprint.strategy.selector(&self.config.drawing);
+        let player_order = self.get_players_in_join_order();
+        if let (Some(round), Some(selector)) = (self.rounds.last().filter(|_| self.telephone.is_none()), selector) {
+            let selected = player_order.iter()
+                .filter_map(|player_id| round.get_data().get(player_id).map(|data| (player_id, data)))
                 .map(|(player_id, data)| {
-                    let imprint = imprint_selector::random(data.drawing.clone(), data.imprint.clone(), 3);
-                    (player_id.clone(), imprint)
+                    let strokes = imprint_selector::select(
+                        selector.as_ref(), data.drawing.clone(), data.imprint.clone(), imprint.amount, &mut self.rng);
+                    (*player_id, strokes)
                 })
                 .collect();
+            imprint_map = imprint_mapper::random(selected, imprint.allow_own_imprint, &mut self.rng);
         }
 
-        self.curr_round = Some(self.curr_round.map_or(1, |v| v + 1));
-        self.rounds.push(
-            Round::new(
+        let curr_round = self.curr_round.map_or(1, |v| v + 1);
+        let difficulty = self.settings.difficulty_curve.difficulty_for_round(curr_round, self.settings.rounds);
+        let suggestion_deadline = SystemTime::now()
+            + Duration::from_secs(self.config.suggestion_selection_seconds.into());
+        let round = match &self.telephone {
+            // Later rounds of a chain draw what the previous player described
+            Some(telephone) if curr_round > 1 => Round::with_suggestions(
+                self.players.clone(),
+                telephone.get_drawing_prompts(curr_round),
+                self.config.max_votes_per_round,
+            ),
+            _ => Round::new(
                 self.players.clone(),
-                &mut self.drawing_suggestions_deck.as_mut().expect("Deck should be init after start_game"),
+                player_order.iter().map(|player_id| {
+                    let deck = self.drawing_suggestions_deck.as_mut().expect("Deck should be init after start_game");
+                    Round::deal_suggestions(deck, difficulty, self.settings.suggestion_choices, &mut self.rng)
+                        .map(|choices| (*player_id, choices))
+                        .ok_or(StartGameError::NotEnoughDrawingSuggestions)
+                }).collect::<Result<_, _>>()?,
+                Some(suggestion_deadline),
                 &imprint_map,
-            ));
+                self.config.max_votes_per_round,
+            ),
+        };
+        self.curr_round = Some(curr_round);
+        self.rounds.push(round);
 
         self.state = GameState::DrawingPhase;
+        self.start_phase_timer(self.settings.drawing_phase_time_limit_seconds);
         self.set_all_player_states(PlayerState::Drawing);
         self.broadcast_lobby_update();
         self.send_drawing_parameters();
+        Ok(())
     }
 
-    fn go_to_voting_phase_if_drawing_is_done(&mut self) {
+    fn end_drawing_phase_if_done(&mut self) {
         let round = self.get_current_round().expect("round should exist");
         if round.is_done_drawing() {
-            self.send_voting_ballots();
-            self.state = GameState::VotingPhase;
-            self.set_all_player_states(PlayerState::Voting);
-            self.broadcast_lobby_update()
+            self.end_drawing_phase();
+        }
+    }
+
+    /***
+     * Moves on with whatever drawings were submitted. Telephone chains go
+     * on to be described, or end after their last drawing. Every other
+     * mode votes on the drawings.
+     */
+    fn end_drawing_phase(&mut self) {
+        let curr_round = self.curr_round.expect("round should exist");
+        if let Some(telephone) = self.telephone.as_mut() {
+            let round = self.rounds.last().expect("round should exist");
+            let drawings = round.get_data().iter().map(|(id, data)| (*id, data.drawing.clone())).collect();
+            let suggestions = round.get_data().iter().map(|(id, data)| (*id, data.drawing_suggestion.clone())).collect();
+            telephone.add_drawings(curr_round, drawings, suggestions);
+            if curr_round == self.settings.rounds {
+                self.finish_round();
+            } else {
+                self.go_to_describing_phase();
+            }
+        } else {
+            self.go_to_voting_phase();
+        }
+    }
+
+    fn go_to_describing_phase(&mut self) {
+        self.state = GameState::DescribingPhase;
+        self.start_phase_timer(self.settings.describing_phase_time_limit_seconds);
+        self.set_all_player_states(PlayerState::Describing);
+        self.broadcast_lobby_update();
+        self.send_describing_parameters();
+    }
+
+    fn end_describing_phase_if_done(&mut self) {
+        let round = self.curr_round.expect("round should exist");
+        let telephone = self.telephone.as_ref().expect("telephone chains should exist in a describing phase");
+        let is_done = self.players.iter()
+            .filter(|(_, p)| !p.borrow().is_disconnected)
+            .all(|(id, _)| telephone.has_described(id, round));
+        if is_done {
+            self.end_describing_phase();
         }
     }
 
+    /***
+     * Moves on to the next drawing with whatever descriptions were submitted
+     */
+    fn end_describing_phase(&mut self) {
+        let round = self.curr_round.expect("round should exist");
+        self.telephone.as_mut()
+            .expect("telephone chains should exist in a describing phase")
+            .fill_missing_descriptions(round);
+        self.finish_round();
+    }
+
+    /***
+     * Moves to voting with whatever drawings were submitted
+     */
+    fn go_to_voting_phase(&mut self) {
+        self.state = GameState::VotingPhase;
+        self.start_phase_timer(self.settings.voting_phase_time_limit_seconds);
+        self.send_voting_ballots();
+        self.set_all_player_states(PlayerState::Voting);
+        self.broadcast_lobby_update()
+    }
+
     fn finish_round_if_voting_phase_is_done(&mut self) {
         let round = self.get_current_round().expect("round should exist");
         if round.is_done_voting() {
-        let scores = round.get_scores();
-            self.add_to_score(&scores);
-
-            // this is the last round, go to results
-            if self.curr_round == Some(self.settings.rounds) {
-                self.state = GameState::Results;
-                self.set_all_player_states(PlayerState::NotReady);
-
-
-                let best_drawing_data = self.rounds.iter()
-                    .map(|r| r.get_data().values())
-                    .flatten()
-                    .filter(|data| data.drawing.is_some())
-                    .max_by(|ldata, rdata| ldata.votes.cmp(&rdata.votes))
-                    .expect("there should be at least one drawing if the voting phase is finishing");
-                let results = Results {
-                    message_name: "results".to_string(),
-                    highest_rated_drawing: best_drawing_data.drawing.as_ref().expect("drawing should exist").to_owned().to_vec(),
-                    imprint: best_drawing_data.imprint.as_ref().map(|i| i.as_ref().clone()),
-                    num_votes: best_drawing_data.votes,
-                    drawing_suggestion: best_drawing_data.drawing_suggestion.clone(),
-                };
-                // Important: send scores before sending the results
-                self.broadcast_lobby_update();
-                self.broadcast_results(results);
-            } else {
-                self.start_next_round();
+            self.finish_round();
+        }
+    }
+
+    /***
+     * Tallies the votes that were submitted and moves to the next round,
+     * or to the results if this was the last round
+     */
+    fn finish_round(&mut self) {
+        let round = self.get_current_round().expect("round should exist");
+        let scores = round.get_scores(self.settings.scoring_rule.rule().as_ref());
+        self.add_to_score(&scores);
+
+        // this is the last round, go to results
+        if self.curr_round == Some(self.settings.rounds) {
+            self.go_to_results();
+        } else if let Err(e) = self.start_next_round() {
+            // The deck only ever reshuffles, so this shouldn't happen after the first round
+            error!("Couldn't start round {:?} of room {}, ending the game: {:?}",
+                self.curr_round.map(|r| r + 1), self.room_code, e);
+            self.go_to_results();
+        }
+    }
+
+    fn go_to_results(&mut self) {
+        self.state = GameState::Results;
+        self.phase_timer = None;
+        self.set_all_player_states(PlayerState::NotReady);
+
+        // A timed out game may not have any drawings at all
+        let best_drawing_data = self.rounds.iter()
+            .flat_map(|r| r.get_data().values())
+            .filter(|data| data.drawing.is_some())
+            .max_by(|ldata, rdata| ldata.votes.cmp(&rdata.votes));
+        let results = Results {
+            message_name: "results".to_string(),
+            highest_rated_drawing: best_drawing_data
+                .and_then(|data| data.drawing.as_ref())
+                .map(|d| d.to_vec())
+                .unwrap_or_default(),
+            imprint: best_drawing_data.and_then(|data| data.imprint.as_ref()).map(|i| i.as_ref().clone()),
+            num_votes: best_drawing_data.map_or(0, |data| data.votes),
+            drawing_suggestion: best_drawing_data.map(|data| data.drawing_suggestion.clone()).unwrap_or_default(),
+            highest_rated_drawing_id: best_drawing_data.map(|data| data.drawing_id),
+            winning_teams: Some(teams::get_winning_teams(&self.players, self.settings.num_teams))
+                .filter(|_| self.settings.mode == GameMode::Teams),
+            telephone_chains: self.telephone.as_ref().map(|t| t.to_views(|id| {
+                self.players.get(id).map(|p| p.borrow().name.clone()).unwrap_or_default()
+            })),
+            game_id: self.save_to_history(),
+        };
+        // Important: send scores before sending the results
+        self.broadcast_lobby_update();
+        self.broadcast_results(results.clone());
+        self.results = Some(results);
+    }
+
+    /***
+     * Saves the finished game on the blocking thread pool, so that a slow
+     * disk doesn't hold up every other room. Returns the id the game is
+     * saved under.
+     */
+    fn save_to_history(&self) -> Uuid {
+        let record = self.to_record(Uuid::new_v4());
+        let game_id = record.id;
+        let room_code = self.room_code.clone();
+        let history = self.history.clone();
+        actix_web::rt::spawn(async move {
+            match web::block(move || history.save(&record)).await {
+                Ok(Ok(_)) => info!("Saved game {} from room {}", game_id, room_code),
+                Ok(Err(e)) => error!("Failed to save game from room {}: {:?}", room_code, e),
+                Err(e) => error!("Failed to save game from room {}: {}", room_code, e),
             }
+        });
+        game_id
+    }
+
+    fn to_record(&self, id: Uuid) -> GameRecord {
+        GameRecord {
+            id,
+            room_code: self.room_code.clone(),
+            finished_at_unix_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis() as u64,
+            settings: self.settings.clone(),
+            seed: Some(self.seed),
+            players: self.players.values()
+                .map(|p| PlayerRecord { name: p.borrow().name.clone(), score: p.borrow().score })
+                .collect(),
+            rounds: self.rounds.iter().enumerate()
+                .map(|(i, round)| RoundRecord {
+                    round: i + 1,
+                    drawings: round.get_data().iter()
+                        .map(|(player_id, data)| DrawingRecord {
+                            drawing_id: data.drawing_id,
+                            player_name: round.get_player_name(player_id).unwrap_or_default(),
+                            drawing_suggestion: data.drawing_suggestion.clone(),
+                            drawing: data.drawing.as_ref().map(|d| (**d).clone()),
+                            imprint: data.imprint.as_ref().map(|i| (**i).clone()),
+                            votes: data.votes,
+                        })
+                        .collect(),
+                })
+                .collect(),
         }
+    }
 
+    fn start_phase_timer(&mut self, time_limit_seconds: Option<u32>) {
+        self.phase_timer = time_limit_seconds.map(|seconds|
+            PhaseTimer::new(self.curr_round.expect("round should exist"), self.state.clone(), seconds));
+        if let Some(timer) = &self.phase_timer {
+            self.unscheduled_phase_timers.push(timer.clone());
+        }
     }
 
     fn add_to_score(&mut self, scores: &HashMap<Uuid, i32>) {
@@ -427,40 +1184,69 @@ impl Game{
 // Messaging
 impl Game{
     pub fn broadcast_lobby_update(&self) {
-        info!("Broadcasting lobby update to all players");
-        for player in self.players.values() {
-            self.send_lobby_update_to_player(&player.borrow().client);
+        info!("Broadcasting lobby update to all players and spectators");
+        for client in self.get_all_clients() {
+            self.send_lobby_update_to_player(&client);
         }
     }
 
     pub fn broadcast_settings_update(&self) {
-        info!("Broadcasting settings update to all players");
-        for player in self.players.values() {
-            self.send_settings_update_to_player(&player.borrow().client);
+        info!("Broadcasting settings update to all players and spectators");
+        for client in self.get_all_clients() {
+            self.send_settings_update_to_player(&client);
         }
     }
 
     pub fn broadcast_results(&self, results: Results) {
-        info!("Broadcasting results to all players");
-        for player in self.players.values() {
-            self.send_results_to_player(&player.borrow().client, results.clone());
+        info!("Broadcasting results to all players and spectators");
+        for client in self.get_all_clients() {
+            self.send_results_to_player(&client, results.clone());
         }
     }
 
     pub fn send_drawing_parameters(&self) {
+        for player in self.players.values() {
+            self.send_drawing_parameters_to_player(&player.borrow().client);
+        }
+    }
+
+    fn send_drawing_parameters_to_player(&self, client_connection: &ClientConnection) {
         let round = self.get_current_round().unwrap();
+        client_connection.actor_addr.do_send(
+            DrawingParameters {
+                message_name: "drawing_parameters".to_string(),
+                round: self.curr_round.unwrap(),
+                canvas_width: self.config.drawing.canvas_width,
+                canvas_height: self.config.drawing.canvas_height,
+                drawing_suggestion:
+                    round.get_drawing_suggestion(&client_connection.id).unwrap().clone(),
+                suggestion_choices: round.get_suggestion_choices(&client_connection.id).cloned().unwrap_or_default(),
+                suggestion_deadline: round.get_suggestion_deadline().map(PhaseDeadline::from_system_time),
+                imprint: round.get_imprint(&client_connection.id).map(|i| (*i).clone()),
+                deadline: self.phase_timer.as_ref().map(|t| t.to_deadline()),
+            }
+        )
+    }
+
+    fn send_describing_parameters(&self) {
         for player in self.players.values() {
-            player.borrow().client.actor_addr.do_send(
-                DrawingParameters {
-                    message_name: "drawing_parameters".to_string(),
-                    round: self.curr_round.unwrap(),
-                    drawing_suggestion:
-                        round.get_drawing_suggestion(&player.borrow().client.id).unwrap().clone(),
-                    imprint: round.get_imprint(&player.borrow().client.id).map(|i| (*i).clone()),
-                }
-            )
+            self.send_describing_parameters_to_player(&player.borrow().client);
         }
+    }
 
+    fn send_describing_parameters_to_player(&self, client_connection: &ClientConnection) {
+        let round = self.curr_round.expect("round should exist");
+        let telephone = self.telephone.as_ref().expect("telephone chains should exist in a describing phase");
+        client_connection.actor_addr.do_send(
+            DescribingParameters {
+                message_name: "describing_parameters".to_string(),
+                round,
+                drawing: telephone.get_drawing_to_describe(&client_connection.id, round)
+                    .map(|d| (*d).clone())
+                    .unwrap_or_default(),
+                deadline: self.phase_timer.as_ref().map(|t| t.to_deadline()),
+            }
+        )
     }
 
     fn send_lobby_update_to_player(&self, client_connection: &ClientConnection) {
@@ -470,14 +1256,75 @@ impl Game{
                 room_code: self.room_code.clone(),
                 state: self.state.clone(),
                 round: self.curr_round,
-                players: self.players.iter().map(
-                    |(id, player)|
-                        player.borrow().to_view(self.is_host(id), *id == client_connection.id)
-                ).collect(),
+                players: self.get_player_views(&client_connection.id),
+                spectator_count: self.spectators.len(),
+                teams: self.get_team_views(),
+                is_public: self.privacy.is_public,
+                has_password: self.privacy.password.is_some(),
             }
         );
     }
 
+    fn get_player_views(&self, client_id: &Uuid) -> Vec<PlayerView> {
+        self.players.iter().map(
+            |(id, player)|
+                player.borrow().to_view(self.is_host(id), id == client_id)
+        ).collect()
+    }
+
+    fn get_team_views(&self) -> Option<Vec<teams::TeamView>> {
+        Some(teams::get_team_views(&self.players, self.settings.num_teams))
+            .filter(|_| self.settings.mode == GameMode::Teams)
+    }
+
+    /***
+     * Sends a client everything it needs to catch up with the game,
+     * in case it missed some of the messages sent at phase changes
+     */
+    pub fn send_game_snapshot(&self, client_id: &Uuid) -> Result<(), GetGameStateError> {
+        let client_connection = self.get_all_clients().into_iter()
+            .find(|c| c.id == *client_id)
+            .ok_or(GetGameStateError::ClientIsNotInAGame)?;
+        client_connection.actor_addr.do_send(self.get_game_snapshot(client_id));
+        Ok(())
+    }
+
+    fn get_game_snapshot(&self, client_id: &Uuid) -> GameSnapshot {
+        let is_player = self.players.contains_key(client_id);
+        let round = self.get_current_round().filter(|_| self.state != GameState::WaitingForPlayers);
+        let own_round = round.filter(|_| is_player);
+        // Spectators aren't in any chain
+        let drawing_to_describe = self.telephone.as_ref()
+            .zip(self.curr_round)
+            .filter(|_| is_player && self.state == GameState::DescribingPhase)
+            .and_then(|(telephone, r)| telephone.get_drawing_to_describe(client_id, r))
+            .map(|d| (*d).clone());
+        let ballot = (self.state == GameState::VotingPhase)
+            .then(|| self.get_voting_ballot(client_id, &self.get_full_ballot()));
+
+        GameSnapshot {
+            message_name: "game_snapshot".to_string(),
+            room_code: self.room_code.clone(),
+            settings: self.settings.clone(),
+            state: self.state.clone(),
+            round: self.curr_round,
+            players: self.get_player_views(client_id),
+            spectator_count: self.spectators.len(),
+            teams: self.get_team_views(),
+            deadline: self.phase_timer.as_ref().map(|t| t.to_deadline()),
+            canvas_width: self.config.drawing.canvas_width,
+            canvas_height: self.config.drawing.canvas_height,
+            drawing_suggestion: own_round.and_then(|r| r.get_drawing_suggestion(client_id)).cloned(),
+            suggestion_choices: own_round.and_then(|r| r.get_suggestion_choices(client_id)).cloned(),
+            suggestion_deadline: own_round.and_then(|r| r.get_suggestion_deadline()).map(PhaseDeadline::from_system_time),
+            imprint: own_round.and_then(|r| r.get_imprint(client_id)).map(|i| (*i).clone()),
+            drawing: own_round.and_then(|r| r.get_drawing(client_id)).map(|d| (*d).clone()),
+            drawing_to_describe,
+            ballot,
+            results: self.results.clone(),
+        }
+    }
+
     fn send_settings_update_to_player(&self, client_connection: &ClientConnection) {
         client_connection.actor_addr.do_send(
             GameSettingsUpdate {
@@ -492,22 +1339,25 @@ impl Game{
     }
 
     fn send_voting_ballots(&self) {
+        let full_ballot = self.get_full_ballot();
+        for client in self.get_all_clients() {
+            self.send_voting_ballots_to_player(&client, &full_ballot);
+        }
+    }
+
+    fn get_full_ballot(&self) -> HashMap<&Uuid, BallotItem> {
         let round = self.get_current_round().unwrap();
         let data = round.get_data();
-        let full_ballot: HashMap<&Uuid, BallotItem> =
-            data.iter().map(|(player_id, round_data)| {
-                let b = BallotItem {
-                    id: round_data.drawing_id.clone(),
-                    suggestion: round_data.drawing_suggestion.clone(),
-                    drawing: round_data.drawing.as_ref().map(|d| (**d).clone()).unwrap_or_default(),
-                    imprint: round_data.imprint.as_ref().map(|i| (**i).clone()).unwrap_or_default(),
-                };
-                (player_id, b)
-            }).collect();
-
-        for player in self.players.values() {
-            self.send_voting_ballots_to_player(&player.borrow().client, &full_ballot);
-        }
+        data.iter().map(|(player_id, round_data)| {
+            let b = BallotItem {
+                id: round_data.drawing_id,
+                suggestion: Some(round_data.drawing_suggestion.clone())
+                    .filter(|_| self.settings.mode != GameMode::Guessing),
+                drawing: round_data.drawing.as_ref().map(|d| (**d).clone()).unwrap_or_default(),
+                imprint: round_data.imprint.as_ref().map(|i| (**i).clone()).unwrap_or_default(),
+            };
+            (player_id, b)
+        }).collect()
     }
 
     fn send_voting_ballots_to_player(
@@ -515,17 +1365,233 @@ impl Game{
         client_connection: &ClientConnection,
         full_ballot: &HashMap<&Uuid, BallotItem>
     ) {
-        let ballot: Vec<VotableBallotItem> = full_ballot.iter()
-                .map(|(player_id, ballot_item)|
-                    VotableBallotItem{
-                        ballot_item: (*ballot_item).clone(),
-                        is_voting_enabled: **player_id != client_connection.id,
-                        } )
-                .collect();
         client_connection.actor_addr.do_send(VotingBallot {
             message_name: "voting_ballot".to_string(),
             round: self.curr_round.unwrap(),
-            ballot: ballot,
+            ballot: self.get_voting_ballot(&client_connection.id, full_ballot),
+            deadline: self.phase_timer.as_ref().map(|t| t.to_deadline()),
+        })
+    }
+
+    fn get_voting_ballot(&self, client_id: &Uuid, full_ballot: &HashMap<&Uuid, BallotItem>) -> Vec<VotableBallotItem> {
+        // Spectators get the ballot too, but can't vote on any of it
+        let is_player = self.players.contains_key(client_id);
+        let round = self.get_current_round();
+        full_ballot.iter()
+            .map(|(player_id, ballot_item)|
+                VotableBallotItem{
+                    ballot_item: (*ballot_item).clone(),
+                    is_voting_enabled: is_player && *player_id != client_id
+                        && !round.is_some_and(|r| r.are_teammates(client_id, player_id)),
+                    } )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, path::Path};
+
+    use actix::{dev::channel, Addr};
+
+    use crate::history::storage::StorageError;
+
+    use super::{*, super::drawing::Stroke};
+
+    #[derive(Debug)]
+    struct NoHistory;
+
+    impl GameHistoryStorage for NoHistory {
+        fn save(&self, _record: &GameRecord) -> Result<(), StorageError> {
+            Ok(())
+        }
+
+        fn get(&self, _game_id: &Uuid) -> Result<Option<GameRecord>, StorageError> {
+            Ok(None)
+        }
+    }
+
+    /// A connection whose messages are dropped
+    fn connect_from(ip: &str) -> Rc<ClientConnection> {
+        let (tx, _) = channel::channel(16);
+        Rc::new(ClientConnection {
+            id: Uuid::new_v4(),
+            peer_addr: SocketAddr::new(ip.parse().unwrap(), 8000),
+            actor_addr: Addr::new(tx),
         })
     }
-}
\ No newline at end of file
+
+    fn connect() -> Rc<ClientConnection> {
+        connect_from("127.0.0.1")
+    }
+
+    /// A game with its players in join order, the host first, and one spectator
+    fn new_game(mode: GameMode, rounds: usize, num_players: usize) -> (Game, Vec<Uuid>, Uuid) {
+        let host = connect();
+        let decks = DeckRepository::load(Path::new("decks")).expect("decks should load");
+        let mut game = Game::new("ABCD".to_string(), host.clone(), "player 0".to_string(), RoomPrivacy::default(),
+            Arc::new(NoHistory), Rc::new(GameConfig::default()), Arc::new(decks));
+        game.settings.mode = mode;
+        game.settings.rounds = rounds;
+        let mut players = vec![host.id];
+        for i in 1..num_players {
+            let client = connect();
+            game.add_player(client.clone(), &format!("player {}", i)).unwrap();
+            players.push(client.id);
+        }
+        let spectator = connect();
+        game.add_spectator(spectator.clone());
+        (game, players, spectator.id)
+    }
+
+    fn assert_spectator_snapshot(game: &Game, spectator: &Uuid, state: GameState) {
+        assert_eq!(game.state, state);
+        let snapshot = game.get_game_snapshot(spectator);
+        assert_eq!(snapshot.state, state);
+        assert!(snapshot.drawing_suggestion.is_none());
+        assert!(snapshot.drawing.is_none());
+        assert!(snapshot.drawing_to_describe.is_none());
+        assert_eq!(snapshot.ballot.is_some(), state == GameState::VotingPhase);
+        assert_eq!(snapshot.results.is_some(), state == GameState::Results);
+    }
+
+    #[actix_web::test]
+    async fn spectators_get_a_snapshot_in_every_voting_game_phase() {
+        let (mut game, players, spectator) = new_game(GameMode::Default, 1, 3);
+        assert_spectator_snapshot(&game, &spectator, GameState::WaitingForPlayers);
+
+        game.start_game(&players[0]).unwrap();
+        assert_spectator_snapshot(&game, &spectator, GameState::DrawingPhase);
+        assert!(game.get_game_snapshot(&players[0]).drawing_suggestion.is_some());
+
+        for player_id in &players {
+            game.submit_drawing(player_id, vec![], 1).unwrap();
+        }
+        assert_spectator_snapshot(&game, &spectator, GameState::VotingPhase);
+
+        for player_id in &players {
+            game.submit_vote(player_id, HashMap::new()).unwrap();
+        }
+        assert_spectator_snapshot(&game, &spectator, GameState::Results);
+    }
+
+    #[actix_web::test]
+    async fn spectators_get_a_snapshot_in_every_telephone_phase() {
+        let (mut game, players, spectator) = new_game(GameMode::Telephone, 2, 3);
+        game.start_game(&players[0]).unwrap();
+        assert_spectator_snapshot(&game, &spectator, GameState::DrawingPhase);
+
+        for player_id in &players {
+            game.submit_drawing(player_id, vec![], 1).unwrap();
+        }
+        assert_spectator_snapshot(&game, &spectator, GameState::DescribingPhase);
+        assert!(game.get_game_snapshot(&players[0]).drawing_to_describe.is_some());
+
+        for player_id in &players {
+            game.submit_description(player_id, 1, "a cat").unwrap();
+        }
+        assert_spectator_snapshot(&game, &spectator, GameState::DrawingPhase);
+
+        for player_id in &players {
+            game.submit_drawing(player_id, vec![], 2).unwrap();
+        }
+        assert_spectator_snapshot(&game, &spectator, GameState::Results);
+    }
+
+    /***
+     * Plays the first round of a seeded game and returns, in join order,
+     * each player's suggestions in both rounds and their imprint in the second
+     */
+    fn play_seeded_round(seed: u64) -> Vec<String> {
+        let (mut game, players, _) = new_game(GameMode::Default, 2, 4);
+        game.settings.seed = Some(seed);
+        game.settings.suggestion_choices = 3;
+        game.start_game(&players[0]).unwrap();
+        for (i, player_id) in players.iter().enumerate() {
+            let drawing = (0..6).map(|j| Stroke {
+                coordinates: vec![(i as f32 * 100.0, j as f32 * 100.0), (i as f32 * 100.0 + 50.0, j as f32 * 100.0)],
+                brush_size: 5,
+                color: "#000000".to_string(),
+            }).collect();
+            game.submit_drawing(player_id, drawing, 1).unwrap();
+        }
+        for player_id in &players {
+            game.submit_vote(player_id, HashMap::new()).unwrap();
+        }
+        assert_eq!(game.curr_round, Some(2));
+        assert!(players.iter().all(|player_id| game.rounds[1].get_imprint(player_id).is_some()));
+        players.iter()
+            .map(|player_id| serde_json::to_string(&(
+                game.rounds[0].get_suggestion_choices(player_id),
+                game.rounds[1].get_suggestion_choices(player_id),
+                game.rounds[1].get_imprint(player_id).map(|i| (*i).clone()),
+            )).unwrap())
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn same_seed_deals_the_same_suggestions_and_imprints() {
+        assert_eq!(play_seeded_round(42), play_seeded_round(42));
+        assert_ne!(play_seeded_round(42), play_seeded_round(43));
+    }
+
+    #[test]
+    fn banned_players_cant_join_again_by_name_or_from_their_address() {
+        let (mut game, players, _) = new_game(GameMode::Default, 1, 1);
+        game.add_player(connect_from("10.0.0.2"), "Mallory").unwrap();
+        game.ban_player(&players[0], "Mallory").unwrap();
+
+        assert!(matches!(game.add_player(connect(), " mallory "), Err(JoinGameError::PlayerIsBanned)));
+        assert!(matches!(game.add_player(connect_from("10.0.0.2"), "Eve"), Err(JoinGameError::PlayerIsBanned)));
+        game.add_player(connect_from("10.0.0.3"), "Eve").unwrap();
+    }
+
+    #[test]
+    fn addresses_are_not_banned_when_turned_off() {
+        let (mut game, players, _) = new_game(GameMode::Default, 1, 1);
+        Rc::get_mut(&mut game.config).unwrap().ban_addresses = false;
+        game.add_player(connect_from("10.0.0.2"), "Mallory").unwrap();
+        game.ban_player(&players[0], "Mallory").unwrap();
+
+        assert!(matches!(game.add_player(connect(), "Mallory"), Err(JoinGameError::PlayerIsBanned)));
+        game.add_player(connect_from("10.0.0.2"), "Eve").unwrap();
+    }
+
+    #[actix_web::test]
+    async fn votes_are_only_taken_once_per_player_while_voting() {
+        let (mut game, players, _) = new_game(GameMode::Default, 1, 3);
+        game.start_game(&players[0]).unwrap();
+        assert!(matches!(game.submit_vote(&players[0], HashMap::new()), Err(SubmitVoteError::NotInVotingPhase)));
+
+        for player_id in &players {
+            game.submit_drawing(player_id, vec![], 1).unwrap();
+        }
+        game.submit_vote(&players[0], HashMap::new()).unwrap();
+        assert!(matches!(game.submit_vote(&players[0], HashMap::new()), Err(SubmitVoteError::VoteAlreadySubmitted)));
+
+        for player_id in &players[1..] {
+            game.submit_vote(player_id, HashMap::new()).unwrap();
+        }
+        assert_eq!(game.state, GameState::Results);
+        assert!(matches!(game.submit_vote(&players[0], HashMap::new()), Err(SubmitVoteError::NotInVotingPhase)));
+    }
+
+    #[actix_web::test]
+    async fn telephone_goes_on_after_a_player_is_removed_before_drawing() {
+        let (mut game, players, _) = new_game(GameMode::Telephone, 2, 3);
+        game.start_game(&players[0]).unwrap();
+        game.kick_player(&players[0], "player 2").unwrap();
+
+        for player_id in &players[..2] {
+            game.submit_drawing(player_id, vec![], 1).unwrap();
+        }
+        assert_eq!(game.state, GameState::DescribingPhase);
+
+        game.submit_description(&players[0], 1, "a cat").unwrap();
+        assert!(matches!(game.submit_description(&players[0], 1, "a dog"),
+            Err(SubmitDescriptionError::DescriptionWasAlreadySubmitted)));
+        game.submit_description(&players[1], 1, "a dog").unwrap();
+        assert_eq!(game.state, GameState::DrawingPhase);
+        assert_eq!(game.curr_round, Some(2));
+    }
+}
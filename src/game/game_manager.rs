@@ -1,49 +1,91 @@
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 
-use log::{trace, warn, info};
+use log::{trace, warn, info, error};
 use uuid::Uuid;
 
-use crate::game::{drawing::Drawing,game::Game, room_code_generator::RoomCodeGenerator};
+use crate::game::{drawing::Drawing,game::{Game, RoomPrivacy}, room_code_generator::RoomCodeGenerator};
+use crate::api::{list_public_rooms::RoomInfo, server_messages::room_closed::RoomClosedReason};
+use crate::config::GameConfig;
+use crate::history::storage::GameHistoryStorage;
+use crate::game::deck_repository::DeckRepository;
 
-use super::{game_settings::GameSettings, errors::*};
+use super::{game_settings::GameSettings, phase_timer::PhaseTimer, round::GuessResult, errors::*};
+
+const MAX_PASSWORD_LENGTH: usize = 64;
 
 pub struct GameManager {
     room_code_generator: RoomCodeGenerator,
     games_by_room_code: std::collections::HashMap<String, Game>,
     room_code_by_client_id: std::collections::HashMap<Uuid, String>,
+    // Spectators are tracked separately so that they can't make player requests
+    room_code_by_spectator_id: std::collections::HashMap<Uuid, String>,
+    history: Arc<dyn GameHistoryStorage>,
+    config: Rc<GameConfig>,
+    decks: Arc<DeckRepository>,
 }
 
 impl GameManager {
-    pub fn new() -> Self {
+    pub fn new(
+        history: Arc<dyn GameHistoryStorage>,
+        decks: Arc<DeckRepository>,
+        room_code_generator: RoomCodeGenerator,
+        config: GameConfig,
+    ) -> Self {
         GameManager {
-            room_code_generator: RoomCodeGenerator::new(4),
+            room_code_generator,
             games_by_room_code: std::collections::HashMap::new(),
             room_code_by_client_id: std::collections::HashMap::new(),
+            room_code_by_spectator_id: std::collections::HashMap::new(),
+            history,
+            config: Rc::new(config),
+            decks,
         }
     }
 
+    /***
+     * Every request a player makes goes through here, so it also counts as
+     * activity in the game
+     */
     pub fn get_game_mut(&mut self, client_id: &Uuid) -> Option<&mut Game>{
         let room_code = self.room_code_by_client_id.get(client_id)?;
-        self.games_by_room_code.get_mut(room_code)
+        let game = self.games_by_room_code.get_mut(room_code)?;
+        game.touch();
+        Some(game)
     }
 
     pub fn create_game(
         &mut self,
         client_connection: Rc<crate::client_connection::ClientConnection>,
         name: String,
+        mut privacy: RoomPrivacy,
     )
-    -> Result<(), CreateGameError> {
+    -> Result<Uuid, CreateGameError> {
         if self.is_already_in_a_game(&client_connection.id) {
             return Err(CreateGameError::ClientIsAlreadyInAGame);
         }
-        let room_code = self.room_code_generator.generate();
+        privacy.password = privacy.password.filter(|p| !p.is_empty());
+        if privacy.password.as_ref().is_some_and(|p| p.chars().count() > MAX_PASSWORD_LENGTH) {
+            return Err(CreateGameError::InvalidPassword);
+        }
+        let room_code = self.room_code_generator.generate(
+            self.games_by_room_code.len(),
+            |code| self.games_by_room_code.contains_key(code))
+            .ok_or_else(|| {
+                error!("Couldn't find a free room code with {} live rooms", self.games_by_room_code.len());
+                CreateGameError::NoRoomCodeAvailable
+            })?;
 
-        let game = Game::new(room_code.clone(), client_connection.clone(), name);
+        let game = Game::new(
+            room_code.clone(), client_connection.clone(), name, privacy,
+            self.history.clone(), self.config.clone(), self.decks.clone());
+        let reconnect_token = game.get_reconnect_token(&client_connection.id).expect("host should exist");
         self.games_by_room_code.insert(room_code.clone(), game);
         self.room_code_by_client_id.insert(client_connection.id, room_code.clone());
         info!("# of games: {}", self.games_by_room_code.len());
-        Ok(())
+        Ok(reconnect_token)
     }
 
     pub fn join_game(
@@ -51,54 +93,173 @@ impl GameManager {
         client_connection: Rc<crate::client_connection::ClientConnection>,
         room_code: &str,
         proposed_name: &str,
-    ) -> Result<(), JoinGameError> {
+        password: Option<&str>,
+    ) -> Result<Uuid, JoinGameError> {
         if self.is_already_in_a_game(&client_connection.id) {
             return Err(JoinGameError::ClientIsAlreadyInAGame);
         }
 
         trace!("Games: {:?}", self.games_by_room_code);
         let game = self.games_by_room_code.get_mut(room_code).ok_or(JoinGameError::RoomDoesNotExist)?;
-        self.room_code_by_client_id.insert(client_connection.id, room_code.to_string());
-        game.add_player(client_connection, proposed_name)
+        game.touch();
+        if !game.is_password_correct(password) {
+            return Err(JoinGameError::WrongPassword);
+        }
+        let client_id = client_connection.id;
+        let reconnect_token = game.add_player(client_connection, proposed_name)?;
+        self.room_code_by_client_id.insert(client_id, room_code.to_string());
+        Ok(reconnect_token)
+    }
+
+    pub fn join_as_spectator(
+        &mut self,
+        client_connection: Rc<crate::client_connection::ClientConnection>,
+        room_code: &str,
+        password: Option<&str>,
+    ) -> Result<(), JoinAsSpectatorError> {
+        if self.is_already_in_a_game(&client_connection.id) {
+            return Err(JoinAsSpectatorError::ClientIsAlreadyInAGame);
+        }
+
+        let game = self.games_by_room_code.get_mut(room_code).ok_or(JoinAsSpectatorError::RoomDoesNotExist)?;
+        game.touch();
+        if !game.is_password_correct(password) {
+            return Err(JoinAsSpectatorError::WrongPassword);
+        }
+        self.room_code_by_spectator_id.insert(client_connection.id, room_code.to_string());
+        game.add_spectator(client_connection);
+        Ok(())
+    }
+
+    pub fn rejoin_game(
+        &mut self,
+        client_connection: Rc<crate::client_connection::ClientConnection>,
+        room_code: &str,
+        reconnect_token: &Uuid,
+    ) -> Result<(), RejoinGameError> {
+        if self.is_already_in_a_game(&client_connection.id) {
+            return Err(RejoinGameError::ClientIsAlreadyInAGame);
+        }
+
+        let game = self.games_by_room_code.get_mut(room_code).ok_or(RejoinGameError::RoomDoesNotExist)?;
+        game.touch();
+        let client_id = client_connection.id;
+        game.rejoin_player(client_connection, reconnect_token)?;
+        self.room_code_by_client_id.insert(client_id, room_code.to_string());
+        Ok(())
+    }
+
+    /***
+     * Public rooms, sorted by room code
+     */
+    pub fn list_public_rooms(&self) -> Vec<RoomInfo> {
+        let mut rooms: Vec<RoomInfo> = self.games_by_room_code.values()
+            .filter_map(|game| game.get_room_info())
+            .collect();
+        rooms.sort_by(|l, r| l.room_code.cmp(&r.room_code));
+        rooms
     }
 
     pub fn update_game_settings(&mut self, client_id: &Uuid, game_settings: &GameSettings)
     -> Result<(), UpdateGameSettingsError> {
-        let game = self.get_game_mut(client_id).ok_or_else(|| UpdateGameSettingsError::ClientIsNotInAGame)?;
-        game.update_settings(client_id, &game_settings)
+        let game = self.get_game_mut(client_id).ok_or(UpdateGameSettingsError::ClientIsNotInAGame)?;
+        game.update_settings(client_id, game_settings)
     }
 
     pub fn set_player_ready(&mut self, client_id: &Uuid, ready_state: bool)
     -> Result<(), SetPlayerReadyError> {
-        let game = self.get_game_mut(client_id).ok_or_else(|| SetPlayerReadyError::ClientIsNotInAGame)?;
+        let game = self.get_game_mut(client_id).ok_or(SetPlayerReadyError::ClientIsNotInAGame)?;
         game.set_player_ready(client_id, ready_state);
         Ok(())
     }
 
     pub fn start_game(&mut self, client_id: &Uuid) -> Result<(), StartGameError> {
-        let game = self.get_game_mut(client_id).ok_or_else(|| StartGameError::ClientIsNotInAGame)?;
+        let game = self.get_game_mut(client_id).ok_or(StartGameError::ClientIsNotInAGame)?;
         game.start_game(client_id)
     }
 
     pub fn play_again(&mut self, client_id: &Uuid) -> Result<(), PlayAgainError> {
-        let game = self.get_game_mut(client_id).ok_or_else(|| PlayAgainError::ClientIsNotInAGame)?;
+        let game = self.get_game_mut(client_id).ok_or(PlayAgainError::ClientIsNotInAGame)?;
         game.play_again(client_id)
     }
 
+    pub fn upload_custom_deck(&mut self, client_id: &Uuid, deck_name: String, words: Vec<String>)
+    -> Result<(), UploadCustomDeckError> {
+        let game = self.get_game_mut(client_id).ok_or(UploadCustomDeckError::ClientIsNotInAGame)?;
+        game.upload_custom_deck(client_id, deck_name, words)
+    }
+
+    pub fn assign_team(&mut self, client_id: &Uuid, player_name: &str, team: usize)
+    -> Result<(), AssignTeamError> {
+        let game = self.get_game_mut(client_id).ok_or(AssignTeamError::ClientIsNotInAGame)?;
+        game.assign_team(client_id, player_name, team)
+    }
+
+    pub fn get_game_state(&mut self, client_id: &Uuid) -> Result<(), GetGameStateError> {
+        // Spectators may ask too. A client resyncing counts as activity.
+        let game = self.room_code_by_client_id.get(client_id)
+            .or_else(|| self.room_code_by_spectator_id.get(client_id))
+            .and_then(|room_code| self.games_by_room_code.get_mut(room_code))
+            .ok_or(GetGameStateError::ClientIsNotInAGame)?;
+        game.touch();
+        game.send_game_snapshot(client_id)
+    }
+
+    pub fn kick_player(&mut self, client_id: &Uuid, player_name: &str) -> Result<(), KickPlayerError> {
+        let game = self.get_game_mut(client_id).ok_or(KickPlayerError::ClientIsNotInAGame)?;
+        let removed_id = game.kick_player(client_id, player_name)?;
+        self.room_code_by_client_id.remove(&removed_id);
+        Ok(())
+    }
+
+    pub fn ban_player(&mut self, client_id: &Uuid, player_name: &str) -> Result<(), BanPlayerError> {
+        let game = self.get_game_mut(client_id).ok_or(BanPlayerError::ClientIsNotInAGame)?;
+        let removed_id = game.ban_player(client_id, player_name)?;
+        self.room_code_by_client_id.remove(&removed_id);
+        Ok(())
+    }
+
+    pub fn transfer_host(&mut self, client_id: &Uuid, player_name: &str) -> Result<(), TransferHostError> {
+        let game = self.get_game_mut(client_id).ok_or(TransferHostError::ClientIsNotInAGame)?;
+        game.transfer_host(client_id, player_name)
+    }
+
     pub fn submit_drawing(&mut self, client_id: &Uuid, drawing: Drawing, round: usize)
     -> Result<(), SubmitDrawingError> {
-        let game = self.get_game_mut(client_id).ok_or_else(|| SubmitDrawingError::ClientIsNotInAGame)?;
+        let game = self.get_game_mut(client_id).ok_or(SubmitDrawingError::ClientIsNotInAGame)?;
         game.submit_drawing(client_id, drawing, round)
     }
 
+    pub fn choose_suggestion(&mut self, client_id: &Uuid, round: usize, choice: usize)
+    -> Result<String, ChooseSuggestionError> {
+        let game = self.get_game_mut(client_id).ok_or(ChooseSuggestionError::ClientIsNotInAGame)?;
+        game.choose_suggestion(client_id, round, choice)
+    }
+
+    pub fn submit_description(&mut self, client_id: &Uuid, round: usize, description: &str)
+    -> Result<(), SubmitDescriptionError> {
+        let game = self.get_game_mut(client_id).ok_or(SubmitDescriptionError::ClientIsNotInAGame)?;
+        game.submit_description(client_id, round, description)
+    }
+
+    pub fn submit_guesses(&mut self, client_id: &Uuid, guesses: HashMap<Uuid, String>)
+    -> Result<HashMap<Uuid, GuessResult>, SubmitGuessesError> {
+        let game = self.get_game_mut(client_id).ok_or(SubmitGuessesError::ClientIsNotInAGame)?;
+        game.submit_guesses(client_id, guesses)
+    }
+
     pub fn submit_vote(&mut self, client_id: &Uuid, votes: HashMap<Uuid, i32>)
     -> Result<(), SubmitVoteError> {
-        let game = self.get_game_mut(client_id).ok_or_else(|| SubmitVoteError::ClientIsNotInAGame)?;
+        let game = self.get_game_mut(client_id).ok_or(SubmitVoteError::ClientIsNotInAGame)?;
         game.submit_vote(client_id, votes)
     }
 
     pub fn remove_player_connection(&mut self, client_id: &Uuid) {
-        if let Some(room_code) = self.room_code_by_client_id.remove(client_id) {
+        if let Some(room_code) = self.room_code_by_spectator_id.remove(client_id) {
+            if let Some(game) = self.games_by_room_code.get_mut(&room_code) {
+                game.remove_spectator(client_id);
+            }
+        } else if let Some(room_code) = self.room_code_by_client_id.remove(client_id) {
             {
                 let game = self.games_by_room_code.get_mut(&room_code).expect("game should exist");
                 game.disconnect_player(client_id)
@@ -107,6 +268,7 @@ impl GameManager {
                 let game = self.games_by_room_code.get(&room_code).expect("game should exist");
                 if game.all_players_disconnected() {
                     self.games_by_room_code.remove(&room_code);
+                    self.room_code_by_spectator_id.retain(|_, r| *r != room_code);
                     info!("# of games: {}", self.games_by_room_code.len())
                 }
             }
@@ -115,7 +277,55 @@ impl GameManager {
         }
     }
 
+    /***
+     * Closes rooms that nobody has used for idle_room_timeout, and removes
+     * players who have been disconnected for longer than the grace period
+     */
+    pub fn sweep(&mut self, idle_room_timeout: Duration, disconnected_player_grace: Duration) {
+        let idle_rooms: Vec<String> = self.games_by_room_code.iter()
+            .filter(|(_, game)| game.get_last_activity().elapsed() >= idle_room_timeout)
+            .map(|(room_code, _)| room_code.clone())
+            .collect();
+        for room_code in idle_rooms {
+            self.close_room(&room_code, RoomClosedReason::Idle);
+        }
+        for game in self.games_by_room_code.values_mut() {
+            game.evict_disconnected_players(disconnected_player_grace);
+        }
+    }
+
+    fn close_room(&mut self, room_code: &str, reason: RoomClosedReason) {
+        if let Some(game) = self.games_by_room_code.remove(room_code) {
+            game.close(reason);
+            self.room_code_by_client_id.retain(|_, r| r != room_code);
+            self.room_code_by_spectator_id.retain(|_, r| r != room_code);
+            info!("# of games: {}", self.games_by_room_code.len())
+        }
+    }
+
+    /***
+     * Returns every phase timer that was started since the last call,
+     * along with the room code of the game it belongs to
+     */
+    pub fn take_unscheduled_phase_timers(&mut self) -> Vec<(String, PhaseTimer)> {
+        self.games_by_room_code.iter_mut()
+            .flat_map(|(room_code, game)| {
+                game.take_unscheduled_phase_timers().into_iter()
+                    .map(|timer| (room_code.clone(), timer))
+            })
+            .collect()
+    }
+
+    pub fn expire_phase_timer(&mut self, room_code: &str, timer_id: &Uuid) {
+        if let Some(game) = self.games_by_room_code.get_mut(room_code) {
+            game.expire_phase_timer(timer_id);
+        } else {
+            trace!("Phase timer expired for a game that no longer exists: {}", room_code);
+        }
+    }
+
     fn is_already_in_a_game(&self, client_id: &Uuid) -> bool {
         self.room_code_by_client_id.contains_key(client_id)
+            || self.room_code_by_spectator_id.contains_key(client_id)
     }
 }
\ No newline at end of file
@@ -2,9 +2,71 @@ use std::collections::HashMap;
 
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+use super::{card::Difficulty, scoring::ScoringRuleKind, imprint_selector::{ImprintAmount, ImprintStrategy}};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameMode {
     Default,
+    // Suggestions are hidden on the ballot, and players guess them instead of voting
+    Guessing,
+    // Each player starts a chain that alternates between drawing and describing
+    // the previous player's drawing. There's no voting.
+    Telephone,
+    // Players are split into teams that share their score, and can't vote for teammates
+    Teams,
+}
+
+/// Which difficulty of drawing suggestion is preferred in each round
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DifficultyCurve {
+    // Any difficulty, in every round
+    #[default]
+    Mixed,
+    Easy,
+    Medium,
+    Hard,
+    // Easy in the first third of the rounds, medium in the second, hard in the last
+    Ramp,
+}
+
+impl DifficultyCurve {
+    /***
+     * The preferred difficulty for a round (1-indexed)
+     */
+    pub fn difficulty_for_round(self, round: usize, total_rounds: usize) -> Option<Difficulty> {
+        match self {
+            DifficultyCurve::Mixed => None,
+            DifficultyCurve::Easy => Some(Difficulty::Easy),
+            DifficultyCurve::Medium => Some(Difficulty::Medium),
+            DifficultyCurve::Hard => Some(Difficulty::Hard),
+            DifficultyCurve::Ramp => match 3 * (round - 1) / total_rounds.max(1) {
+                0 => Some(Difficulty::Easy),
+                1 => Some(Difficulty::Medium),
+                _ => Some(Difficulty::Hard),
+            },
+        }
+    }
+}
+
+/// How the strokes of one round are handed on to the next
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct ImprintSettings {
+    pub strategy: ImprintStrategy,
+    pub amount: ImprintAmount,
+    // Whether a player may be handed back strokes from their own drawing
+    pub allow_own_imprint: bool,
+}
+
+impl Default for ImprintSettings {
+    fn default() -> Self {
+        ImprintSettings {
+            strategy: ImprintStrategy::default(),
+            amount: ImprintAmount::default(),
+            allow_own_imprint: true,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -13,5 +75,32 @@ pub struct GameSettings{
     pub rounds: usize,
     pub drawing_phase_time_limit_seconds: Option<u32>,
     pub voting_phase_time_limit_seconds: Option<u32>,
+    // Only used in Telephone mode
+    #[serde(default)]
+    pub describing_phase_time_limit_seconds: Option<u32>,
     pub drawing_decks_included: HashMap<String, bool>,
+    #[serde(default)]
+    pub difficulty_curve: DifficultyCurve,
+    // Number of suggestions each player may choose from every round
+    #[serde(default = "default_suggestion_choices")]
+    pub suggestion_choices: usize,
+    // Only used in Teams mode
+    #[serde(default = "default_num_teams")]
+    pub num_teams: usize,
+    #[serde(default)]
+    pub scoring_rule: ScoringRuleKind,
+    #[serde(default)]
+    pub imprint: ImprintSettings,
+    // Replaying a game with the same seed and inputs deals the same suggestions
+    // and imprints. A random seed is picked at the start if there's none.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn default_suggestion_choices() -> usize {
+    1
+}
+
+fn default_num_teams() -> usize {
+    2
 }
\ No newline at end of file
@@ -0,0 +1,47 @@
+use strsim::levenshtein;
+
+const ARTICLES: &[&str] = &["a", "an", "the"];
+// A guess may have one typo for every this many characters of the answer
+const CHARACTERS_PER_TYPO: usize = 6;
+
+/***
+ * Whether a guess should count as naming the answer. Ignores case,
+ * punctuation, extra whitespace, leading articles and plurals, and
+ * allows for the odd typo in longer answers.
+ */
+pub fn is_match(guess: &str, answer: &str) -> bool {
+    let guess = normalize(guess);
+    let answer = normalize(answer);
+    if guess.is_empty() {
+        return false;
+    }
+    guess == answer || levenshtein(&guess, &answer) <= answer.chars().count() / CHARACTERS_PER_TYPO
+}
+
+fn normalize(text: &str) -> String {
+    let cleaned: String = text.chars()
+        .filter(|c| *c != '\'')
+        .map(|c| if c.is_alphanumeric() { c.to_lowercase().next().unwrap_or(c) } else { ' ' })
+        .collect();
+    let mut words: Vec<&str> = cleaned.split_whitespace().collect();
+    if words.len() > 1 && ARTICLES.contains(&words[0]) {
+        words.remove(0);
+    }
+    words.into_iter().map(singular).collect::<Vec<_>>().join(" ")
+}
+
+/***
+ * Strips the common English plural endings. Only has to be consistent,
+ * since it's applied to both the guess and the answer.
+ */
+fn singular(word: &str) -> String {
+    if word.len() > 4 && word.ends_with("ies") {
+        format!("{}y", &word[..word.len() - 3])
+    } else if word.len() > 3 && ["ses", "xes", "zes", "ches", "shes"].iter().any(|e| word.ends_with(e)) {
+        word[..word.len() - 2].to_string()
+    } else if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
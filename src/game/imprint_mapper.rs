@@ -1,22 +1,34 @@
 use std::{collections::HashMap, rc::Rc};
 
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
 use uuid::Uuid;
 
 use super::drawing::Drawing;
 
 
 /***
- * Assigns an imprint to a random player (including themselves)
+ * Assigns each imprint to a random player. Unless allow_own is set, nobody
+ * gets their own imprint back, which leaves a lone player without one.
+ * Imprints are passed in a fixed player order so that the same rng state
+ * always hands out the same imprints.
  */
-pub fn random(imprint_map: &HashMap<Uuid, Option<Rc<Drawing>>>)
+pub fn random(imprints: Vec<(Uuid, Option<Rc<Drawing>>)>, allow_own: bool, rng: &mut impl Rng)
     -> HashMap<Uuid, Option<Rc<Drawing>>> {
 
-    let mut imprints: Vec<&Option<Rc<Drawing>>> = imprint_map.values().collect();
-    imprints.shuffle(&mut rand::thread_rng());
+    let players: Vec<Uuid> = imprints.iter().map(|(id, _)| *id).collect();
+    let mut drawings: Vec<Option<Rc<Drawing>>> = imprints.into_iter().map(|(_, d)| d).collect();
+    if allow_own {
+        drawings.shuffle(rng);
+        return players.into_iter().zip(drawings).collect();
+    }
 
-    imprint_map.keys()
-        .map(|k| k.clone())
-        .zip(imprints.into_iter().cloned())
+    // Each player gets the imprint of the next one in a shuffled circle
+    let mut order: Vec<usize> = (0..players.len()).collect();
+    order.shuffle(rng);
+    if order.len() < 2 {
+        return players.into_iter().map(|id| (id, None)).collect();
+    }
+    order.iter().zip(order.iter().cycle().skip(1))
+        .map(|(to, from)| (players[*to], drawings[*from].clone()))
         .collect()
-}
\ No newline at end of file
+}
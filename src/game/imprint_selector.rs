@@ -1,28 +1,181 @@
 use std::rc::Rc;
 
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::config::DrawingConfig;
 
 use super::drawing::{Drawing, Stroke};
 
+// Fraction of the canvas width and height a region crop covers
+const REGION_SIZE: f32 = 0.5;
+
+/// How much of the previous round's strokes are handed on
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImprintAmount {
+    // A number of strokes
+    Strokes(usize),
+    // A fraction (0, 1] of the total length of the strokes
+    Fraction(f32),
+}
+
+impl Default for ImprintAmount {
+    fn default() -> Self {
+        ImprintAmount::Strokes(3)
+    }
+}
+
+impl ImprintAmount {
+    pub fn is_valid(self, max_strokes: usize) -> bool {
+        match self {
+            ImprintAmount::Strokes(n) => n > 0 && n <= max_strokes,
+            ImprintAmount::Fraction(f) => f > 0.0 && f <= 1.0,
+        }
+    }
+
+    /***
+     * Takes strokes from the front until the amount is reached
+     */
+    fn take(self, strokes: Vec<Stroke>) -> Drawing {
+        match self {
+            ImprintAmount::Strokes(n) => strokes.into_iter().take(n).collect(),
+            ImprintAmount::Fraction(f) => {
+                let target = f * strokes.iter().map(stroke_length).sum::<f32>();
+                let mut taken = 0.0;
+                strokes.into_iter()
+                    .take_while(|s| {
+                        let keep = taken < target;
+                        taken += stroke_length(s);
+                        keep
+                    })
+                    .collect()
+            },
+        }
+    }
+}
+
+/***
+ * Picks the strokes of a drawing that are handed on as an imprint.
+ * Strokes are passed oldest first.
+ */
+pub trait ImprintSelector {
+    fn select(&self, strokes: Vec<Stroke>, amount: ImprintAmount, rng: &mut dyn RngCore) -> Drawing;
+}
+
+/// The imprint selectors a host can pick from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImprintStrategy {
+    #[default]
+    Random,
+    Longest,
+    // The strokes that fall in a random part of the canvas
+    Region,
+    MostRecent,
+    // Players always start from a blank canvas
+    None,
+}
+
+impl ImprintStrategy {
+    pub fn selector(self, config: &DrawingConfig) -> Option<Box<dyn ImprintSelector>> {
+        match self {
+            ImprintStrategy::Random => Some(Box::new(RandomStrokes)),
+            ImprintStrategy::Longest => Some(Box::new(LongestStrokes)),
+            ImprintStrategy::Region => Some(Box::new(RegionCrop {
+                canvas_width: config.canvas_width,
+                canvas_height: config.canvas_height,
+            })),
+            ImprintStrategy::MostRecent => Some(Box::new(MostRecentStrokes)),
+            ImprintStrategy::None => None,
+        }
+    }
+}
+
+fn stroke_length(stroke: &Stroke) -> f32 {
+    stroke.coordinates.windows(2)
+        .map(|w| ((w[1].0 - w[0].0).powi(2) + (w[1].1 - w[0].1).powi(2)).sqrt())
+        .sum()
+}
 
+pub struct RandomStrokes;
+
+impl ImprintSelector for RandomStrokes {
+    fn select(&self, mut strokes: Vec<Stroke>, amount: ImprintAmount, rng: &mut dyn RngCore) -> Drawing {
+        strokes.shuffle(rng);
+        amount.take(strokes)
+    }
+}
+
+pub struct LongestStrokes;
+
+impl ImprintSelector for LongestStrokes {
+    fn select(&self, mut strokes: Vec<Stroke>, amount: ImprintAmount, _rng: &mut dyn RngCore) -> Drawing {
+        strokes.sort_by(|l, r| stroke_length(r).total_cmp(&stroke_length(l)));
+        amount.take(strokes)
+    }
+}
+
+pub struct MostRecentStrokes;
+
+impl ImprintSelector for MostRecentStrokes {
+    fn select(&self, mut strokes: Vec<Stroke>, amount: ImprintAmount, _rng: &mut dyn RngCore) -> Drawing {
+        strokes.reverse();
+        let mut selected = amount.take(strokes);
+        selected.reverse();
+        selected
+    }
+}
 
 /***
- * Chooses n strokes from drawing + imprint, and returns it as a Drawing
+ * Crops the strokes to a random rectangle of the canvas. Strokes that leave
+ * the rectangle are split into the pieces inside it.
  */
-pub fn random(
+pub struct RegionCrop {
+    pub canvas_width: f32,
+    pub canvas_height: f32,
+}
+
+impl ImprintSelector for RegionCrop {
+    fn select(&self, strokes: Vec<Stroke>, amount: ImprintAmount, rng: &mut dyn RngCore) -> Drawing {
+        let (width, height) = (self.canvas_width * REGION_SIZE, self.canvas_height * REGION_SIZE);
+        let left = rng.gen_range(0.0..=self.canvas_width - width);
+        let top = rng.gen_range(0.0..=self.canvas_height - height);
+        let is_inside = |(x, y): &(f32, f32)| {
+            (left..=left + width).contains(x) && (top..=top + height).contains(y)
+        };
+
+        let pieces = strokes.into_iter().flat_map(|stroke| {
+            stroke.coordinates
+                .split(|c| !is_inside(c))
+                .filter(|piece| !piece.is_empty())
+                .map(|piece| Stroke {
+                    coordinates: piece.to_vec(),
+                    brush_size: stroke.brush_size,
+                    color: stroke.color.clone(),
+                })
+                .collect::<Vec<_>>()
+        }).collect();
+        amount.take(pieces)
+    }
+}
+
+/***
+ * Chooses strokes from drawing + imprint, and returns them as a Drawing.
+ * None if there's nothing to choose from.
+ */
+pub fn select(
+    selector: &dyn ImprintSelector,
     drawing: Option<Rc<Drawing>>,
     imprint: Option<Rc<Drawing>>,
-    n: usize) -> Option<Rc<Drawing>> {
-    let mut rng = rand::thread_rng();
-
-    if drawing.is_some() && imprint.is_some() {
-        let combined: &[Stroke] = &[&drawing.unwrap()[..], &imprint.unwrap()[..]].concat();
-        Some(Rc::new(combined.choose_multiple(&mut rng, n).cloned().collect()))
-    } else if drawing.is_some() {
-        Some(Rc::new(drawing.unwrap().choose_multiple(&mut rng, n).cloned().collect()))
-    } else if imprint.is_some() {
-        Some(Rc::new(imprint.unwrap().choose_multiple(&mut rng, n).cloned().collect()))
-    } else {
-        None
-    }
-}
\ No newline at end of file
+    amount: ImprintAmount,
+    rng: &mut dyn RngCore) -> Option<Rc<Drawing>> {
+    if drawing.is_none() && imprint.is_none() {
+        return None;
+    }
+    // The imprint was on the canvas before anything the player drew
+    let strokes: Vec<Stroke> = imprint.iter().chain(drawing.iter())
+        .flat_map(|d| d.iter().cloned())
+        .collect();
+    Some(Rc::new(selector.select(strokes, amount, rng)))
+}
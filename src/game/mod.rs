@@ -1,14 +1,23 @@
+pub mod color;
 pub mod drawing;
+pub mod drawing_validator;
+pub mod guess_matcher;
 pub mod imprint_selector;
 pub mod imprint_mapper;
 
+#[allow(clippy::module_inception)]
 pub mod game;
 pub mod game_manager;
 pub mod game_settings;
+pub mod phase_timer;
 pub mod player_view;
 pub mod room_code_generator;
 pub mod round;
+pub mod scoring;
+pub mod teams;
+pub mod telephone;
 
+pub mod card;
 pub mod deck;
 pub mod deck_repository;
 
@@ -0,0 +1,58 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use super::game::GameState;
+
+/// What the client sees of a phase timer
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhaseDeadline {
+    // Unix timestamp (in milliseconds) of when the phase ends on the server
+    pub deadline_unix_ms: u64,
+    // Seconds left in the phase when this message was sent, rounded up
+    pub seconds_remaining: u64,
+}
+
+impl PhaseDeadline {
+    pub fn from_system_time(deadline: SystemTime) -> Self {
+        let remaining = deadline
+            .duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO);
+        PhaseDeadline {
+            deadline_unix_ms: deadline
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis() as u64,
+            seconds_remaining: remaining.as_secs_f64().ceil() as u64,
+        }
+    }
+}
+
+/// A deadline for a single phase of a single round
+#[derive(Debug, Clone)]
+pub struct PhaseTimer {
+    // Unique per phase, so a timer that fires late can be recognized as stale
+    pub id: Uuid,
+    pub round: usize,
+    pub state: GameState,
+    pub duration: Duration,
+    pub deadline: SystemTime,
+}
+
+impl PhaseTimer {
+    pub fn new(round: usize, state: GameState, seconds: u32) -> Self {
+        let duration = Duration::from_secs(seconds.into());
+        PhaseTimer {
+            id: Uuid::new_v4(),
+            round,
+            state,
+            duration,
+            deadline: SystemTime::now() + duration,
+        }
+    }
+
+    pub fn to_deadline(&self) -> PhaseDeadline {
+        PhaseDeadline::from_system_time(self.deadline)
+    }
+}
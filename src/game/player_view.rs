@@ -1,6 +1,7 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Instant};
 
 use serde::{Serialize, Deserialize};
+use uuid::Uuid;
 
 use crate::websocket::client_connection::ClientConnection;
 
@@ -10,6 +11,8 @@ pub enum PlayerState {
     Ready,
     Drawing,
     DrawingDone,
+    Describing,
+    DescribingDone,
     Voting,
     VotingDone,
 }
@@ -26,6 +29,9 @@ pub struct PlayerView {
     pub is_you: bool,
     // if this player's client connection closed mid-game
     pub is_disconnected: bool,
+    // Only in Teams mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -38,17 +44,26 @@ pub struct Player{
     pub state: PlayerState,
     pub score: i32,
     pub is_disconnected: bool,
+    // When the player's connection closed, while they're disconnected
+    pub disconnected_at: Option<Instant>,
+    // Secret handed to the client so that it can rejoin after disconnecting
+    pub reconnect_token: Uuid,
+    // Index of the player's team, in Teams mode
+    pub team: Option<usize>,
 }
 
 impl Player{
     pub fn new(client: Rc<ClientConnection>, name: String, number: usize) -> Player {
         Player {
-            client: client,
-            name: name,
+            client,
+            name,
             host_rank: number,
             state: PlayerState::NotReady,
             score: 0,
             is_disconnected: false,
+            disconnected_at: None,
+            reconnect_token: Uuid::new_v4(),
+            team: None,
         }
     }
 
@@ -60,6 +75,7 @@ impl Player{
             is_host,
             is_you,
             is_disconnected: self.is_disconnected,
+            team: self.team,
         }
     }
 }
@@ -1,34 +1,141 @@
-use block_id::{Alphabet, BlockId};
+use rand::seq::SliceRandom;
 
-use std::time::{SystemTime, UNIX_EPOCH};
+// Codes get longer once more than this fraction of them are in use
+const MAX_OCCUPANCY: f64 = 0.01;
+// Random draws at one length before giving up on it and trying a longer one
+const MAX_ATTEMPTS_PER_LENGTH: usize = 32;
+// How much longer than the configured length codes may get before giving up
+const MAX_EXTRA_LENGTH: usize = 8;
 
-#[derive(Clone)]
+/***
+ * Letter combinations that never appear in a code: ones that read as
+ * other letters, and ones that spell something rude. Checked on top of
+ * the blocklist in the config.
+ */
+const BLOCKED_SUBSTRINGS: &[&str] = &[
+    "RN", "VV", "CL",
+    "ASS", "CUM", "FAG", "FCK", "FUK", "FUCK", "KKK", "NAZI", "PISS", "POO", "RAPE",
+    "SEX", "SHT", "SHIT", "SLUT", "TIT", "TWAT", "WTF", "DCK", "DICK", "COCK", "CNT", "CUNT",
+];
+
+/***
+ * Draws random room codes, so that live rooms can't be guessed from one
+ * another. Codes start at the configured length and grow as the server fills up.
+ */
+#[derive(Clone, Debug)]
 pub struct RoomCodeGenerator {
-    seed: u128,
-    count: usize,
-    length: u8
+    alphabet: Vec<char>,
+    min_length: usize,
+    // Uppercased, like the codes
+    blocklist: Vec<String>,
 }
 
 impl RoomCodeGenerator {
-    pub fn new(length: u8) -> Self {
-        let start = SystemTime::now();
-        let timestamp = start
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs() as u128;
-        RoomCodeGenerator{
-            seed: timestamp,
-            count: 0,
-            length: length,
+    pub fn new(min_length: u8, alphabet: &str, blocklist: &[String]) -> Self {
+        let blocklist: Vec<String> = BLOCKED_SUBSTRINGS.iter().map(|s| s.to_string())
+            .chain(blocklist.iter().map(|s| s.to_ascii_uppercase()))
+            .collect();
+        RoomCodeGenerator {
+            alphabet: usable_letters(alphabet, &blocklist),
+            min_length: min_length.into(),
+            blocklist,
         }
     }
 
-    pub fn generate(&mut self) -> String {
-        let generator = BlockId::new(
-            Alphabet::lowercase_alpha(), self.seed, self.length);
+    /***
+     * A code that isn't taken. `live_rooms` is how many codes are taken.
+     * None if the blocklist and taken codes leave nothing to draw, even
+     * at the longest length allowed.
+     */
+    pub fn generate(&self, live_rooms: usize, is_taken: impl Fn(&str) -> bool) -> Option<String> {
+        let mut rng = rand::thread_rng();
+        let max_length = self.min_length + MAX_EXTRA_LENGTH;
+        let mut length = self.min_length;
+        while length < max_length && (live_rooms + 1) as f64 > self.capacity(length) * MAX_OCCUPANCY {
+            length += 1;
+        }
+        for length in length..=max_length {
+            for _ in 0..MAX_ATTEMPTS_PER_LENGTH {
+                let code: String = (0..length)
+                    .map(|_| self.alphabet.choose(&mut rng).copied())
+                    .collect::<Option<String>>()?;
+                if !self.is_blocked(&code) && !is_taken(&code) {
+                    return Some(code);
+                }
+            }
+        }
+        None
+    }
 
-        self.count += 1;
+    fn capacity(&self, length: usize) -> f64 {
+        (self.alphabet.len() as f64).powi(length as i32)
+    }
 
-        return generator.encode_string(self.count as u64).to_ascii_uppercase();
+    fn is_blocked(&self, code: &str) -> bool {
+        self.blocklist.iter().any(|blocked| code.contains(blocked.as_str()))
     }
-}
\ No newline at end of file
+}
+
+/***
+ * The letters of `alphabet` that codes can use: uppercased, without
+ * duplicates, and without letters that are blocked on their own
+ */
+pub fn usable_letters(alphabet: &str, blocklist: &[String]) -> Vec<char> {
+    let mut letters: Vec<char> = alphabet.to_ascii_uppercase().chars()
+        .filter(|c| !blocklist.iter().any(|blocked| blocked.eq_ignore_ascii_case(&c.to_string())))
+        .collect();
+    letters.sort();
+    letters.dedup();
+    letters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALPHABET: &str = "ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+    #[test]
+    fn codes_use_the_alphabet_and_skip_blocked_words() {
+        let generator = RoomCodeGenerator::new(3, "ast", &["TT".to_string()]);
+        for _ in 0..1000 {
+            let code = generator.generate(0, |_| false).expect("should find a code");
+            assert!(code.chars().all(|c| "AST".contains(c)), "{}", code);
+            assert!(!code.contains("ASS") && !code.contains("TT"), "{}", code);
+        }
+    }
+
+    #[test]
+    fn single_letter_entries_remove_letters_from_the_alphabet() {
+        let blocklist = ["b".to_string(), "CD".to_string()];
+        assert_eq!(usable_letters("aabBcd", &blocklist), vec!['A', 'C', 'D']);
+        let generator = RoomCodeGenerator::new(4, "AB", &["A".to_string()]);
+        assert!(generator.generate(0, |_| false).is_some_and(|code| code.chars().all(|c| c == 'B')));
+    }
+
+    #[test]
+    fn codes_get_longer_as_rooms_fill_up() {
+        let generator = RoomCodeGenerator::new(4, ALPHABET, &[]);
+        assert_eq!(generator.generate(0, |_| false).map(|c| c.len()), Some(4));
+        // 24^4 codes at 1% occupancy fit about 3300 rooms
+        assert_eq!(generator.generate(5000, |_| false).map(|c| c.len()), Some(5));
+    }
+
+    #[test]
+    fn taken_codes_are_skipped() {
+        let generator = RoomCodeGenerator::new(4, "AB", &[]);
+        for _ in 0..100 {
+            let code = generator.generate(0, |code| code.starts_with('A')).expect("should find a code");
+            assert!(code.starts_with('B'), "{}", code);
+        }
+    }
+
+    #[test]
+    fn gives_up_when_everything_is_blocked() {
+        let pairs = ["AA", "AB", "BA", "BB"].map(String::from);
+        assert_eq!(RoomCodeGenerator::new(2, "AB", &pairs).generate(0, |_| false), None);
+        let letters = ["A", "B"].map(String::from);
+        assert_eq!(RoomCodeGenerator::new(2, "AB", &letters).generate(0, |_| false), None);
+        assert_eq!(RoomCodeGenerator::new(2, "AB", &[]).generate(0, |_| true), None);
+    }
+}
@@ -1,53 +1,136 @@
-use std::{collections::HashMap, rc::Rc, cell::RefCell};
+use std::{collections::HashMap, rc::Rc, cell::RefCell, time::SystemTime};
 
 use log::info;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{drawing::Drawing, deck::Deck, imprint_mapper, player_view::Player, errors::SubmitVoteError};
+use super::{drawing::Drawing, deck::Deck, card::Difficulty, player_view::Player, guess_matcher,
+    scoring::ScoringRule,
+    errors::{SubmitVoteError, ChooseSuggestionError, SubmitGuessesError}};
 
-const MAX_VOTES_PER_ROUND: i32 = 3;
+const MAX_GUESS_LENGTH: usize = 100;
+// Points for the guesser, for each drawing they guessed right
+const POINTS_PER_CORRECT_GUESS: i32 = 2;
+
+/// How a single guess turned out
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuessResult {
+    pub guess: String,
+    pub drawing_suggestion: String,
+    pub is_correct: bool,
+}
 
 // TODO: this struct doesn't really make sense
 #[derive(Debug, Clone)]
 pub struct RoundDataPerPlayer {
     pub drawing_id: Uuid,
+    // The suggestion the player is drawing, one of suggestion_choices
     pub drawing_suggestion: String,
+    pub suggestion_choices: Vec<String>,
+    pub has_chosen_suggestion: bool,
     pub imprint: Option<Rc<Drawing>>,
     pub drawing: Option<Rc<Drawing>>,
     pub has_voted: bool,
+    // Points this player gave to each drawing, keyed by drawing id
+    pub ballot: HashMap<Uuid, i32>,
+    // In Guessing mode, the number of players who guessed this drawing
     pub votes: i32,
+    // Number of other drawings this player guessed right
+    pub correct_guesses: i32,
 }
 
 #[derive(Debug, Clone)]
 pub struct Round {
     round_data_per_player: HashMap<Uuid, RoundDataPerPlayer>,
     players: HashMap<Uuid, Rc<RefCell<Player>>>,
+    // Most votes a player may hand out in this round
+    max_votes: i32,
+    // When players can no longer change their suggestion. None if there's nothing to choose.
+    suggestion_deadline: Option<SystemTime>,
 }
 
 impl Round {
     pub fn new(
         players: HashMap<Uuid, Rc<RefCell<Player>>>,
-        suggestion_deck: &mut Deck<String>,
-        imprint_map: &HashMap<Uuid, Option<Rc<Drawing>>>,
+        // Dealt with deal_suggestions, at least one for every player
+        mut suggestion_choices: HashMap<Uuid, Vec<String>>,
+        suggestion_deadline: Option<SystemTime>,
+        imprints: &HashMap<Uuid, Option<Rc<Drawing>>>,
+        max_votes: i32,
+    ) -> Round {
+        let has_choices = suggestion_choices.values().any(|c| c.len() > 1);
+        Round {
+            round_data_per_player:
+                players.keys().map(|id| {
+                    let suggestion_choices = suggestion_choices.remove(id).expect("player should be dealt suggestions");
+                    (*id, RoundDataPerPlayer{
+                        drawing_id: Uuid::new_v4(),
+                        drawing_suggestion: suggestion_choices[0].clone(),
+                        has_chosen_suggestion: false,
+                        suggestion_choices,
+                        imprint: imprints.get(id).and_then(|x| x.clone()),
+                        drawing: None,
+                        has_voted: false,
+                        ballot: HashMap::new(),
+                        votes: 0,
+                        correct_guesses: 0,
+                    })
+                }).collect(),
+            players,
+            max_votes,
+            suggestion_deadline: suggestion_deadline.filter(|_| has_choices),
+        }
+    }
 
+    /***
+     * A round where every player draws the suggestion they're given
+     */
+    pub fn with_suggestions(
+        players: HashMap<Uuid, Rc<RefCell<Player>>>,
+        suggestions: HashMap<Uuid, String>,
+        max_votes: i32,
     ) -> Round {
-        let selected_imprints = imprint_mapper::random(imprint_map);
         Round {
             round_data_per_player:
-                players.keys().map(|id|
-                    (id.clone(), RoundDataPerPlayer{
+                players.keys().map(|id| {
+                    let suggestion = suggestions.get(id).cloned().unwrap_or_default();
+                    (*id, RoundDataPerPlayer{
                         drawing_id: Uuid::new_v4(),
-                        drawing_suggestion: suggestion_deck.draw_card().unwrap(),
-                        imprint: selected_imprints.get(&id).and_then(|x| x.clone()),
+                        drawing_suggestion: suggestion.clone(),
+                        suggestion_choices: vec![suggestion],
+                        has_chosen_suggestion: false,
+                        imprint: None,
                         drawing: None,
                         has_voted: false,
+                        ballot: HashMap::new(),
                         votes: 0,
+                        correct_guesses: 0,
                     })
-                ).collect(),
+                }).collect(),
             players,
+            max_votes,
+            suggestion_deadline: None,
         }
     }
 
+    /***
+     * Draws up to `count` different suggestions, and always at least one.
+     * Returns None if the deck has no cards.
+     */
+    pub fn deal_suggestions(deck: &mut Deck, difficulty: Option<Difficulty>, count: usize, rng: &mut impl Rng)
+    -> Option<Vec<String>> {
+        let mut choices: Vec<String> = vec![];
+        for _ in 0..count.max(1) {
+            let card = deck.draw_card(difficulty, rng)?;
+            // A small deck may have been reshuffled while dealing
+            if !choices.contains(&card.text) {
+                choices.push(card.text);
+            }
+        }
+        Some(choices)
+    }
+
     pub fn get_data(&self) -> &HashMap<Uuid, RoundDataPerPlayer> {
         &self.round_data_per_player
     }
@@ -56,12 +139,47 @@ impl Round {
         self.round_data_per_player.get(client_id).map(|data| &data.drawing_suggestion)
     }
 
+    pub fn get_suggestion_choices(&self, client_id: &Uuid) -> Option<&Vec<String>> {
+        self.round_data_per_player.get(client_id).map(|data| &data.suggestion_choices)
+    }
+
+    pub fn get_suggestion_deadline(&self) -> Option<SystemTime> {
+        self.suggestion_deadline
+    }
+
+    /***
+     * Swaps the player's suggestion for another one they were dealt. Each
+     * player gets one choice, before the selection window closes.
+     */
+    pub fn choose_suggestion(&mut self, client_id: &Uuid, choice: usize)
+    -> Result<String, ChooseSuggestionError> {
+        let deadline = self.suggestion_deadline;
+        let data = self.round_data_per_player.get_mut(client_id).expect("player should exist");
+        if data.drawing.is_some() {
+            return Err(ChooseSuggestionError::DrawingWasAlreadySubmitted);
+        }
+        if data.has_chosen_suggestion {
+            return Err(ChooseSuggestionError::SuggestionAlreadyChosen);
+        }
+        if deadline.is_none_or(|d| SystemTime::now() > d) {
+            return Err(ChooseSuggestionError::SelectionWindowClosed);
+        }
+        let suggestion = data.suggestion_choices.get(choice).ok_or(ChooseSuggestionError::InvalidChoice)?.clone();
+        data.drawing_suggestion = suggestion.clone();
+        data.has_chosen_suggestion = true;
+        Ok(suggestion)
+    }
+
     pub fn get_imprint(&self, client_id: &Uuid) -> Option<Rc<Drawing>> {
-        self.round_data_per_player.get(client_id).map(|i| i.imprint.clone()).flatten()
+        self.round_data_per_player.get(client_id).and_then(|i| i.imprint.clone())
+    }
+
+    pub fn get_player_name(&self, client_id: &Uuid) -> Option<String> {
+        self.players.get(client_id).map(|p| p.borrow().name.clone())
     }
 
     pub fn get_drawing(&self, client_id: &Uuid) -> Option<Rc<Drawing>> {
-        self.round_data_per_player.get(client_id).map(|s| s.drawing.clone()).flatten()
+        self.round_data_per_player.get(client_id).and_then(|s| s.drawing.clone())
     }
 
     pub fn set_drawing(&mut self, client_id: &Uuid, drawing: Rc<Drawing>) {
@@ -70,11 +188,29 @@ impl Round {
         player_data.drawing = Some(drawing);
     }
 
-    pub fn submit_vote(&mut self, client_id: &Uuid, votes: HashMap<Uuid, i32>)
+    /***
+     * Moves a player's data over to their new client id after they rejoin
+     */
+    pub fn replace_player_id(&mut self, old_id: &Uuid, new_id: &Uuid) {
+        if let Some(data) = self.round_data_per_player.remove(old_id) {
+            self.round_data_per_player.insert(*new_id, data);
+        }
+        if let Some(player) = self.players.remove(old_id) {
+            self.players.insert(*new_id, player);
+        }
+    }
+
+    pub fn submit_vote(&mut self, client_id: &Uuid, votes: HashMap<Uuid, i32>, scoring_rule: &dyn ScoringRule)
     -> Result<(), SubmitVoteError> {
-        if votes.values().sum::<i32>() > MAX_VOTES_PER_ROUND {
-            return Err(SubmitVoteError::MaximumVotesExceeded)
+        if self.round_data_per_player.get(client_id).expect("player should exist").has_voted {
+            return Err(SubmitVoteError::VoteAlreadySubmitted)
+        }
+        // Checked before tallying so that no scoring rule can let a player
+        // take points away from someone else
+        if votes.values().any(|v| *v < 0) {
+            return Err(SubmitVoteError::NegativeVotes)
         }
+        let ballot = scoring_rule.tally(&votes, self.max_votes)?;
         let client_drawing = self.round_data_per_player.get(client_id).expect("player should exist").drawing_id;
         if let Some(v) = votes.get(&client_drawing) {
             if *v > 0 {
@@ -85,15 +221,71 @@ impl Round {
         if votes.keys().any(|id| !drawing_ids.contains(id)) {
             return Err(SubmitVoteError::InvalidDrawingId) // TODO include the drawing id in the error
         }
+        let voted_for_teammate = self.round_data_per_player.iter()
+            .filter(|(player_id, data)| *player_id != client_id && votes.get(&data.drawing_id).is_some_and(|v| *v > 0))
+            .any(|(player_id, _)| self.are_teammates(client_id, player_id));
+        if voted_for_teammate {
+            return Err(SubmitVoteError::ClientVotedForTeammate)
+        }
 
         for data in self.round_data_per_player.values_mut() {
-            data.votes += votes.get(&data.drawing_id).unwrap();
+            data.votes += ballot.get(&data.drawing_id).unwrap_or(&0);
         }
         let player_data = self.round_data_per_player.get_mut(client_id).unwrap();
+        player_data.ballot = ballot;
         player_data.has_voted = true;
         Ok(())
     }
 
+    /***
+     * Checks each guess against the suggestion of the drawing it's for.
+     * Every drawing guessed right earns its artist a vote.
+     */
+    pub fn submit_guesses(&mut self, client_id: &Uuid, guesses: HashMap<Uuid, String>)
+    -> Result<HashMap<Uuid, GuessResult>, SubmitGuessesError> {
+        let client_data = self.round_data_per_player.get(client_id).expect("player should exist");
+        if client_data.has_voted {
+            return Err(SubmitGuessesError::GuessesAlreadySubmitted);
+        }
+        if guesses.contains_key(&client_data.drawing_id) {
+            return Err(SubmitGuessesError::ClientGuessedOwnDrawing);
+        }
+        if guesses.values().any(|g| g.chars().count() > MAX_GUESS_LENGTH) {
+            return Err(SubmitGuessesError::GuessTooLong);
+        }
+        let drawing_ids: Vec<_> = self.round_data_per_player.values().map(|v| v.drawing_id).collect();
+        if guesses.keys().any(|id| !drawing_ids.contains(id)) {
+            return Err(SubmitGuessesError::InvalidDrawingId);
+        }
+
+        let mut results = HashMap::new();
+        for data in self.round_data_per_player.values_mut() {
+            if let Some(guess) = guesses.get(&data.drawing_id) {
+                let is_correct = guess_matcher::is_match(guess, &data.drawing_suggestion);
+                if is_correct {
+                    data.votes += 1;
+                }
+                results.insert(data.drawing_id, GuessResult {
+                    guess: guess.clone(),
+                    drawing_suggestion: data.drawing_suggestion.clone(),
+                    is_correct,
+                });
+            }
+        }
+        let player_data = self.round_data_per_player.get_mut(client_id).unwrap();
+        player_data.correct_guesses = results.values().filter(|r| r.is_correct).count() as i32;
+        player_data.has_voted = true;
+        Ok(results)
+    }
+
+    /***
+     * Whether both players are on the same team. Always false outside of Teams mode.
+     */
+    pub fn are_teammates(&self, player_id: &Uuid, other_id: &Uuid) -> bool {
+        let team = |id: &Uuid| self.players.get(id).and_then(|p| p.borrow().team);
+        team(player_id).is_some() && team(player_id) == team(other_id)
+    }
+
     /**
      * Returns true if all connected players have submitting a drawing
      */
@@ -120,9 +312,24 @@ impl Round {
             .fold(true, |acc, (_, v)| acc && v.has_voted)
     }
 
+    /***
+     * Forgets a player who left the game for good. A drawing they already
+     * submitted stays, so that it can still be voted on and shown in the
+     * results.
+     */
+    pub fn remove_player(&mut self, player_id: &Uuid) {
+        if self.round_data_per_player.get(player_id).is_some_and(|data| data.drawing.is_none()) {
+            self.round_data_per_player.remove(player_id);
+            self.players.remove(player_id);
+        }
+    }
+
     //TODO type the Uuids
-    pub fn get_scores(&self) -> HashMap<Uuid, i32> {
-        self.round_data_per_player.iter().map(|(player_id, data)|
-            (player_id.clone(), data.votes)).collect()
+    pub fn get_scores(&self, scoring_rule: &dyn ScoringRule) -> HashMap<Uuid, i32> {
+        let mut scores = scoring_rule.score(&self.round_data_per_player);
+        for (player_id, data) in self.round_data_per_player.iter() {
+            *scores.entry(*player_id).or_insert(0) += data.correct_guesses * POINTS_PER_CORRECT_GUESS;
+        }
+        scores
     }
 }
\ No newline at end of file
@@ -0,0 +1,151 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{errors::SubmitVoteError, round::RoundDataPerPlayer};
+
+// Extra points for the artist of the round's top drawing
+const WINNER_BONUS: i32 = 3;
+// Points for a voter who gave any of their votes to the round's top drawing
+const POINTS_FOR_PICKING_WINNER: i32 = 1;
+// Points lost by a player who didn't submit a drawing
+const MISSING_DRAWING_PENALTY: i32 = 2;
+
+/***
+ * Turns the ballots of a round into points. Ballots are tallied as they're
+ * submitted, and the round is scored once everyone has voted.
+ */
+pub trait ScoringRule {
+    /***
+     * Points each drawing gets from one player's ballot, keyed by drawing id.
+     * Vote counts are never negative. By default each vote is worth a point.
+     */
+    fn tally(&self, votes: &HashMap<Uuid, i32>, max_votes: i32)
+    -> Result<HashMap<Uuid, i32>, SubmitVoteError> {
+        // Summed as i64 so that a huge ballot can't overflow
+        if votes.values().map(|v| i64::from(*v)).sum::<i64>() > i64::from(max_votes) {
+            return Err(SubmitVoteError::MaximumVotesExceeded);
+        }
+        Ok(votes.clone())
+    }
+
+    /***
+     * Points each player earned this round, keyed by player id
+     */
+    fn score(&self, round: &HashMap<Uuid, RoundDataPerPlayer>) -> HashMap<Uuid, i32>;
+}
+
+/// The scoring rules a host can pick from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringRuleKind {
+    // A point for every vote a player's drawing got
+    #[default]
+    RawVotes,
+    // Raw votes, plus a bonus for the artist of the top drawing
+    WinnerBonus,
+    // Raw votes, plus a point for voting for the top drawing
+    VoterReward,
+    // Players rank drawings from 1 (best) instead of handing out votes
+    RankedChoice,
+    // Raw votes, minus a penalty for not submitting a drawing
+    MissingDrawingPenalty,
+}
+
+impl ScoringRuleKind {
+    pub fn rule(self) -> Box<dyn ScoringRule> {
+        match self {
+            ScoringRuleKind::RawVotes => Box::new(RawVotes),
+            ScoringRuleKind::WinnerBonus => Box::new(WinnerBonus),
+            ScoringRuleKind::VoterReward => Box::new(VoterReward),
+            ScoringRuleKind::RankedChoice => Box::new(RankedChoice),
+            ScoringRuleKind::MissingDrawingPenalty => Box::new(MissingDrawingPenalty),
+        }
+    }
+}
+
+fn raw_votes(round: &HashMap<Uuid, RoundDataPerPlayer>) -> HashMap<Uuid, i32> {
+    round.iter().map(|(player_id, data)| (*player_id, data.votes)).collect()
+}
+
+/***
+ * Ids of the drawings that got the most votes. Empty if nobody got a vote.
+ */
+fn top_drawings(round: &HashMap<Uuid, RoundDataPerPlayer>) -> HashSet<Uuid> {
+    let most_votes = round.values().map(|data| data.votes).max().unwrap_or(0);
+    round.values()
+        .filter(|data| most_votes > 0 && data.votes == most_votes)
+        .map(|data| data.drawing_id)
+        .collect()
+}
+
+pub struct RawVotes;
+
+impl ScoringRule for RawVotes {
+    fn score(&self, round: &HashMap<Uuid, RoundDataPerPlayer>) -> HashMap<Uuid, i32> {
+        raw_votes(round)
+    }
+}
+
+pub struct WinnerBonus;
+
+impl ScoringRule for WinnerBonus {
+    fn score(&self, round: &HashMap<Uuid, RoundDataPerPlayer>) -> HashMap<Uuid, i32> {
+        let top = top_drawings(round);
+        round.iter().map(|(player_id, data)| {
+            let bonus = if top.contains(&data.drawing_id) { WINNER_BONUS } else { 0 };
+            (*player_id, data.votes + bonus)
+        }).collect()
+    }
+}
+
+pub struct VoterReward;
+
+impl ScoringRule for VoterReward {
+    fn score(&self, round: &HashMap<Uuid, RoundDataPerPlayer>) -> HashMap<Uuid, i32> {
+        let top = top_drawings(round);
+        round.iter().map(|(player_id, data)| {
+            let picked_winner = data.ballot.iter().any(|(id, points)| *points > 0 && top.contains(id));
+            let reward = if picked_winner { POINTS_FOR_PICKING_WINNER } else { 0 };
+            (*player_id, data.votes + reward)
+        }).collect()
+    }
+}
+
+/***
+ * A Borda count: with max_votes of n, a drawing ranked 1st gets n points,
+ * 2nd gets n - 1 and so on. A ballot may rank up to n drawings, and the
+ * ranks must run from 1 without gaps or ties.
+ */
+pub struct RankedChoice;
+
+impl ScoringRule for RankedChoice {
+    fn tally(&self, votes: &HashMap<Uuid, i32>, max_votes: i32)
+    -> Result<HashMap<Uuid, i32>, SubmitVoteError> {
+        if votes.len() > max_votes.max(0) as usize {
+            return Err(SubmitVoteError::MaximumVotesExceeded);
+        }
+        let ranks: HashSet<i32> = votes.values().copied().collect();
+        let is_valid = ranks.len() == votes.len() && (1..=votes.len() as i32).all(|r| ranks.contains(&r));
+        if !is_valid {
+            return Err(SubmitVoteError::InvalidRanking);
+        }
+        Ok(votes.iter().map(|(id, rank)| (*id, max_votes - rank + 1)).collect())
+    }
+
+    fn score(&self, round: &HashMap<Uuid, RoundDataPerPlayer>) -> HashMap<Uuid, i32> {
+        raw_votes(round)
+    }
+}
+
+pub struct MissingDrawingPenalty;
+
+impl ScoringRule for MissingDrawingPenalty {
+    fn score(&self, round: &HashMap<Uuid, RoundDataPerPlayer>) -> HashMap<Uuid, i32> {
+        round.iter().map(|(player_id, data)| {
+            let penalty = if data.drawing.is_none() { MISSING_DRAWING_PENALTY } else { 0 };
+            (*player_id, data.votes - penalty)
+        }).collect()
+    }
+}
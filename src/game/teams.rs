@@ -0,0 +1,78 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::player_view::Player;
+
+/// A team as shown in the lobby
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TeamView {
+    pub team: usize,
+    pub name: String,
+    pub players: Vec<String>,
+    // Sum of the scores of the team's players
+    pub score: i32,
+}
+
+pub fn team_name(team: usize) -> String {
+    format!("Team {}", team + 1)
+}
+
+/***
+ * Puts every player without a valid team on the team with the fewest
+ * players, leaving everyone else where they are. Players are placed in
+ * the order they joined, so the result doesn't depend on map order.
+ */
+pub fn balance(players: &HashMap<Uuid, Rc<RefCell<Player>>>, num_teams: usize) {
+    let mut team_sizes = vec![0; num_teams];
+    let mut unassigned = vec![];
+    for player in players.values() {
+        match player.borrow().team.filter(|t| *t < num_teams) {
+            Some(team) => team_sizes[team] += 1,
+            None => unassigned.push(player.clone()),
+        }
+    }
+    unassigned.sort_by_key(|p| p.borrow().host_rank);
+    for player in unassigned {
+        let (smallest, _) = team_sizes.iter().enumerate()
+            .min_by_key(|(_, size)| **size)
+            .expect("there should be at least one team");
+        team_sizes[smallest] += 1;
+        player.borrow_mut().team = Some(smallest);
+    }
+}
+
+pub fn clear(players: &HashMap<Uuid, Rc<RefCell<Player>>>) {
+    for player in players.values() {
+        player.borrow_mut().team = None;
+    }
+}
+
+pub fn get_team_views(players: &HashMap<Uuid, Rc<RefCell<Player>>>, num_teams: usize) -> Vec<TeamView> {
+    let mut teams: Vec<TeamView> = (0..num_teams)
+        .map(|team| TeamView { team, name: team_name(team), players: vec![], score: 0 })
+        .collect();
+    let mut members: Vec<_> = players.values().collect();
+    members.sort_by_key(|p| p.borrow().host_rank);
+    for player in members {
+        let player = player.borrow();
+        if let Some(team) = player.team.and_then(|t| teams.get_mut(t)) {
+            team.players.push(player.name.clone());
+            team.score += player.score;
+        }
+    }
+    teams
+}
+
+/***
+ * Names of the teams with the highest score. More than one if they tied.
+ */
+pub fn get_winning_teams(players: &HashMap<Uuid, Rc<RefCell<Player>>>, num_teams: usize) -> Vec<String> {
+    let teams = get_team_views(players, num_teams);
+    let best_score = teams.iter().map(|t| t.score).max();
+    teams.into_iter()
+        .filter(|t| Some(t.score) == best_score && !t.players.is_empty())
+        .map(|t| t.name)
+        .collect()
+}
@@ -0,0 +1,284 @@
+use std::{collections::{BTreeMap, HashMap}, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::drawing::Drawing;
+
+/***
+ * Text given to the next player in a chain if the describer didn't
+ * submit anything, and there's no earlier text to fall back on
+ */
+const MISSING_PROMPT: &str = "anything you like";
+
+pub const MAX_DESCRIPTION_LENGTH: usize = 100;
+
+/// One step of a chain
+#[derive(Debug, Clone)]
+pub enum ChainLink {
+    Drawing { player_id: Uuid, drawing: Option<Rc<Drawing>> },
+    Description { player_id: Uuid, text: Option<String> },
+}
+
+#[derive(Debug, Default)]
+struct Chain {
+    // The card the chain started from. None if the player who was dealt
+    // it left before the first drawing phase ended.
+    suggestion: Option<String>,
+    // Keyed by step, so that a missing link never shifts the ones after it
+    links: BTreeMap<usize, ChainLink>,
+}
+
+/// A chain as replayed on the results screen
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChainLinkView {
+    Suggestion { text: String },
+    // drawing is None if the player didn't finish in time
+    Drawing { player_name: String, drawing: Option<Drawing> },
+    Description { player_name: String, text: Option<String> },
+}
+
+/***
+ * The chains of a Telephone game. There's one chain per player, and each
+ * step of a chain is handled by the next player in `player_order`, so
+ * every player works on a different chain at every step.
+ *
+ * Steps alternate between drawing and describing: step 0 is the drawing
+ * phase of round 1, step 1 its describing phase, step 2 the drawing
+ * phase of round 2 and so on.
+ */
+#[derive(Debug)]
+pub struct TelephoneChains {
+    player_order: Vec<Uuid>,
+    chains: Vec<Chain>,
+}
+
+impl TelephoneChains {
+    pub fn new(player_order: Vec<Uuid>) -> Self {
+        TelephoneChains {
+            chains: player_order.iter().map(|_| Chain::default()).collect(),
+            player_order,
+        }
+    }
+
+    pub fn drawing_step(round: usize) -> usize {
+        2 * (round - 1)
+    }
+
+    pub fn describing_step(round: usize) -> usize {
+        2 * (round - 1) + 1
+    }
+
+    /***
+     * What each player should draw at the start of a round after the
+     * first: the last text in the chain handed to them
+     */
+    pub fn get_drawing_prompts(&self, round: usize) -> HashMap<Uuid, String> {
+        let step = Self::drawing_step(round);
+        self.player_order.iter()
+            .map(|player_id| {
+                let chain = &self.chains[self.get_chain_index(player_id, step)];
+                let prompt = chain.links.values().rev()
+                    .find_map(|link| match link {
+                        ChainLink::Description { text, .. } => text.clone(),
+                        ChainLink::Drawing { .. } => None,
+                    })
+                    .or_else(|| chain.suggestion.clone())
+                    .unwrap_or_else(|| MISSING_PROMPT.to_string());
+                (*player_id, prompt)
+            })
+            .collect()
+    }
+
+    /***
+     * Adds the round's drawings to the chains. `suggestions` starts the
+     * chains off in the first round.
+     */
+    pub fn add_drawings(
+        &mut self,
+        round: usize,
+        drawings: HashMap<Uuid, Option<Rc<Drawing>>>,
+        suggestions: HashMap<Uuid, String>,
+    ) {
+        let step = Self::drawing_step(round);
+        for player_id in self.player_order.clone() {
+            let chain_index = self.get_chain_index(&player_id, step);
+            let chain = &mut self.chains[chain_index];
+            if step == 0 {
+                chain.suggestion = suggestions.get(&player_id).cloned();
+            }
+            chain.links.insert(step, ChainLink::Drawing {
+                player_id,
+                drawing: drawings.get(&player_id).cloned().flatten(),
+            });
+        }
+    }
+
+    /***
+     * The drawing a player should describe in the round's describing phase
+     */
+    pub fn get_drawing_to_describe(&self, player_id: &Uuid, round: usize) -> Option<Rc<Drawing>> {
+        let chain = &self.chains[self.get_chain_index(player_id, Self::describing_step(round))];
+        match chain.links.get(&Self::drawing_step(round)) {
+            Some(ChainLink::Drawing { drawing, .. }) => drawing.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn has_described(&self, player_id: &Uuid, round: usize) -> bool {
+        let step = Self::describing_step(round);
+        self.get_link(player_id, step).is_some()
+    }
+
+    pub fn add_description(&mut self, player_id: &Uuid, round: usize, text: String) {
+        let step = Self::describing_step(round);
+        let chain_index = self.get_chain_index(player_id, step);
+        self.chains[chain_index].links.insert(step, ChainLink::Description { player_id: *player_id, text: Some(text) });
+    }
+
+    /***
+     * Ends the round's describing phase, leaving a gap in the chains of
+     * the players who didn't describe their drawing
+     */
+    pub fn fill_missing_descriptions(&mut self, round: usize) {
+        for player_id in self.player_order.clone() {
+            if !self.has_described(&player_id, round) {
+                let step = Self::describing_step(round);
+                let chain_index = self.get_chain_index(&player_id, step);
+                self.chains[chain_index].links.insert(step, ChainLink::Description { player_id, text: None });
+            }
+        }
+    }
+
+    pub fn replace_player_id(&mut self, old_id: &Uuid, new_id: &Uuid) {
+        for id in self.player_order.iter_mut().filter(|id| *id == old_id) {
+            *id = *new_id;
+        }
+        for link in self.chains.iter_mut().flat_map(|chain| chain.links.values_mut()) {
+            match link {
+                ChainLink::Drawing { player_id, .. } | ChainLink::Description { player_id, .. }
+                    if player_id == old_id => *player_id = *new_id,
+                _ => (),
+            }
+        }
+    }
+
+    pub fn to_views(&self, get_player_name: impl Fn(&Uuid) -> String) -> Vec<Vec<ChainLinkView>> {
+        self.chains.iter()
+            .map(|chain| chain.suggestion.iter()
+                .map(|text| ChainLinkView::Suggestion { text: text.clone() })
+                .chain(chain.links.values().map(|link| match link {
+                    ChainLink::Drawing { player_id, drawing } => ChainLinkView::Drawing {
+                        player_name: get_player_name(player_id),
+                        drawing: drawing.as_ref().map(|d| (**d).clone()),
+                    },
+                    ChainLink::Description { player_id, text } => ChainLinkView::Description {
+                        player_name: get_player_name(player_id),
+                        text: text.clone(),
+                    },
+                }))
+                .collect())
+            .collect()
+    }
+
+    /***
+     * Index of the chain the player handles at the given step
+     */
+    fn get_chain_index(&self, player_id: &Uuid, step: usize) -> usize {
+        let n = self.player_order.len();
+        let position = self.player_order.iter().position(|id| id == player_id).expect("player should be in a chain");
+        (position + n - step % n) % n
+    }
+
+    /***
+     * The link the player added to their chain at the given step, if any
+     */
+    fn get_link(&self, player_id: &Uuid, step: usize) -> Option<&ChainLink> {
+        self.chains[self.get_chain_index(player_id, step)].links.get(&step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_ids(n: usize) -> Vec<Uuid> {
+        (0..n).map(|_| Uuid::new_v4()).collect()
+    }
+
+    #[test]
+    fn every_player_has_a_different_chain_at_every_step() {
+        for n in 1..=6 {
+            let players = player_ids(n);
+            let chains = TelephoneChains::new(players.clone());
+            // Past step n, players come back around to chains they already handled
+            for step in 0..3 * n {
+                let mut indexes: Vec<_> = players.iter().map(|id| chains.get_chain_index(id, step)).collect();
+                assert_eq!(indexes[0], (n - step % n) % n);
+                indexes.sort();
+                assert_eq!(indexes, (0..n).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn each_step_is_handed_to_the_next_player() {
+        let players = player_ids(3);
+        let mut chains = TelephoneChains::new(players.clone());
+        // More steps than players, so the chains wrap around
+        for round in 1..=3 {
+            let drawings: HashMap<_, _> = players.iter().map(|id| (*id, Some(Rc::new(vec![])))).collect();
+            let suggestions = if round == 1 {
+                players.iter().enumerate().map(|(i, id)| (*id, format!("card {}", i))).collect()
+            } else {
+                let prompts = chains.get_drawing_prompts(round);
+                for (i, id) in players.iter().enumerate() {
+                    assert_eq!(prompts[id], format!("{} {}", round - 1, (i + 2) % 3));
+                }
+                HashMap::new()
+            };
+            chains.add_drawings(round, drawings.clone(), suggestions);
+
+            for (i, id) in players.iter().enumerate() {
+                let previous_drawing = drawings[&players[(i + 2) % 3]].as_ref().unwrap();
+                let to_describe = chains.get_drawing_to_describe(id, round).unwrap();
+                assert!(Rc::ptr_eq(&to_describe, previous_drawing));
+
+                assert!(!chains.has_described(id, round));
+                chains.add_description(id, round, format!("{} {}", round, i));
+                assert!(chains.has_described(id, round));
+            }
+        }
+
+        let views = chains.to_views(|_| String::new());
+        assert!(views.iter().all(|chain| chain.len() == 7));
+        assert!(matches!(&views[0][0], ChainLinkView::Suggestion { text } if text == "card 0"));
+    }
+
+    #[test]
+    fn a_chain_without_a_suggestion_keeps_its_steps() {
+        let players = player_ids(3);
+        let mut chains = TelephoneChains::new(players.clone());
+        // players[2] left before the first drawing phase ended
+        let drawings = players[..2].iter().map(|id| (*id, Some(Rc::new(vec![])))).collect();
+        let suggestions = players[..2].iter().map(|id| (*id, "cat".to_string())).collect();
+        chains.add_drawings(1, drawings, suggestions);
+
+        // players[0] gets the chain players[2] would have drawn
+        assert!(chains.get_drawing_to_describe(&players[0], 1).is_none());
+        assert!(!chains.has_described(&players[0], 1));
+        chains.add_description(&players[0], 1, "nothing".to_string());
+        assert!(chains.has_described(&players[0], 1));
+        assert!(!chains.has_described(&players[1], 1));
+
+        chains.fill_missing_descriptions(1);
+        assert!(players.iter().all(|id| chains.has_described(id, 1)));
+        let prompts = chains.get_drawing_prompts(2);
+        assert_eq!(prompts[&players[1]], "nothing");
+        assert_eq!(prompts[&players[2]], "cat");
+
+        let views = chains.to_views(|_| String::new());
+        assert_eq!(views.iter().map(|chain| chain.len()).collect::<Vec<_>>(), vec![3, 3, 2]);
+    }
+}
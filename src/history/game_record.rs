@@ -0,0 +1,64 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::game::{drawing::Drawing, game_settings::GameSettings};
+
+/// Everything worth keeping from a finished game
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameRecord {
+    pub id: Uuid,
+    pub room_code: String,
+    pub finished_at_unix_ms: u64,
+    pub settings: GameSettings,
+    // Seed the game was played with, see GameSettings::seed
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub players: Vec<PlayerRecord>,
+    pub rounds: Vec<RoundRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayerRecord {
+    pub name: String,
+    pub score: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoundRecord {
+    // 1-indexed, same as the round number sent to clients
+    pub round: usize,
+    pub drawings: Vec<DrawingRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DrawingRecord {
+    pub drawing_id: Uuid,
+    pub player_name: String,
+    pub drawing_suggestion: String,
+    // None if the player never submitted a drawing
+    pub drawing: Option<Drawing>,
+    pub imprint: Option<Drawing>,
+    pub votes: i32,
+}
+
+impl GameRecord {
+    pub fn get_round(&self, round: usize) -> Option<&RoundRecord> {
+        self.rounds.iter().find(|r| r.round == round)
+    }
+
+    /***
+     * The submitted drawing with the most votes across all rounds
+     */
+    pub fn get_highest_rated_drawing(&self) -> Option<&DrawingRecord> {
+        self.rounds.iter()
+            .flat_map(|r| r.drawings.iter())
+            .filter(|d| d.drawing.is_some())
+            .max_by(|l, r| l.votes.cmp(&r.votes))
+    }
+}
+
+impl RoundRecord {
+    pub fn get_drawing(&self, drawing_id: &Uuid) -> Option<&DrawingRecord> {
+        self.drawings.iter().find(|d| d.drawing_id == *drawing_id)
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use log::warn;
+use uuid::Uuid;
+
+use super::{game_record::GameRecord, storage::{GameHistoryStorage, StorageError}};
+
+const FILE_NAME: &str = "games.jsonl";
+
+/***
+ * Appends each finished game as one line of JSON to a file in the given
+ * directory. Lookups go through an index of where each game's line starts,
+ * which is filled in from the lines appended since the last lookup.
+ */
+#[derive(Debug)]
+pub struct JsonLinesStorage {
+    path: PathBuf,
+    // Serializes appends so that lines from two games never interleave
+    write_lock: Mutex<()>,
+    index: Mutex<Index>,
+}
+
+#[derive(Debug, Default)]
+struct Index {
+    // Byte offset of the line each game was saved on
+    offsets: HashMap<Uuid, u64>,
+    // How much of the file has been indexed. Always the start of a line.
+    indexed_len: u64,
+}
+
+impl JsonLinesStorage {
+    pub fn new(directory: &Path) -> Result<Self, StorageError> {
+        fs::create_dir_all(directory)?;
+        Ok(JsonLinesStorage {
+            path: directory.join(FILE_NAME),
+            write_lock: Mutex::new(()),
+            index: Mutex::new(Index::default()),
+        })
+    }
+
+    /***
+     * Indexes every complete line past indexed_len. Lines that can't be
+     * read as a game, e.g. one cut short by a crash, are logged and
+     * skipped. A last line without a newline may still be being written,
+     * so it's left for the next call.
+     */
+    fn index_new_lines(&self, reader: &mut BufReader<File>, index: &mut Index) -> Result<(), StorageError> {
+        reader.seek(SeekFrom::Start(index.indexed_len))?;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let len = reader.read_until(b'\n', &mut line)?;
+            if len == 0 || line.last() != Some(&b'\n') {
+                return Ok(());
+            }
+            match serde_json::from_slice::<GameRecord>(&line) {
+                Ok(record) => {
+                    index.offsets.insert(record.id, index.indexed_len);
+                },
+                Err(e) => {
+                    warn!("Skipping unreadable line at byte {} of {:?}: {}", index.indexed_len, self.path, e);
+                },
+            }
+            index.indexed_len += len as u64;
+        }
+    }
+}
+
+impl GameHistoryStorage for JsonLinesStorage {
+    fn save(&self, record: &GameRecord) -> Result<(), StorageError> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().expect("lock should not be poisoned");
+        let mut file = OpenOptions::new().create(true).read(true).append(true).open(&self.path)?;
+        // Finish off a line left incomplete by an earlier failed write, so
+        // that it doesn't swallow this one
+        if file.metadata()?.len() > 0 {
+            let mut last_byte = [0u8];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last_byte)?;
+            if last_byte[0] != b'\n' {
+                line.insert(0, '\n');
+            }
+        }
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    fn get(&self, game_id: &Uuid) -> Result<Option<GameRecord>, StorageError> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut reader = BufReader::new(file);
+
+        let offset = {
+            let mut index = self.index.lock().expect("lock should not be poisoned");
+            if !index.offsets.contains_key(game_id) {
+                self.index_new_lines(&mut reader, &mut index)?;
+            }
+            match index.offsets.get(game_id) {
+                Some(offset) => *offset,
+                None => return Ok(None),
+            }
+        };
+
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut line = Vec::new();
+        reader.read_until(b'\n', &mut line)?;
+        Ok(Some(serde_json::from_slice(&line)?))
+    }
+}
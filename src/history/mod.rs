@@ -0,0 +1,3 @@
+pub mod game_record;
+pub mod storage;
+pub mod json_lines_storage;
@@ -0,0 +1,32 @@
+use std::fmt::Debug;
+
+use uuid::Uuid;
+
+use super::game_record::GameRecord;
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(std::io::Error),
+    Serialization(serde_json::Error),
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(value: std::io::Error) -> Self {
+        StorageError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(value: serde_json::Error) -> Self {
+        StorageError::Serialization(value)
+    }
+}
+
+/***
+ * Where finished games are kept. Shared between the game server and the
+ * HTTP routes, so implementations must be thread safe.
+ */
+pub trait GameHistoryStorage: Send + Sync + Debug {
+    fn save(&self, record: &GameRecord) -> Result<(), StorageError>;
+    fn get(&self, game_id: &Uuid) -> Result<Option<GameRecord>, StorageError>;
+}
@@ -1,33 +1,43 @@
+use std::sync::Arc;
+
 use actix::prelude::*;
 use actix_web::{web, App, Error, HttpRequest, HttpResponse, HttpServer};
 use actix_web_actors::ws;
 
-use log::{info};
+use log::{info, error};
 
+use crate::config::ServerConfig;
+use crate::game::deck_repository::DeckRepository;
 use crate::websocket::*;
+use crate::history::{json_lines_storage::JsonLinesStorage, storage::GameHistoryStorage};
 
 mod api;
+mod config;
 mod game;
+mod history;
+mod render;
+mod routes;
 mod websocket;
 
-const MAX_FRAME_SIZE: usize = 524_287; // 512KB
-
 pub async fn ws_route(
     req: HttpRequest,
     stream: web::Payload,
-    server: web::Data<Addr<server::GameServer>>
+    query: web::Query<encoding::ConnectQuery>,
+    server: web::Data<Addr<server::GameServer>>,
+    config: web::Data<ServerConfig>,
 ) -> Result<HttpResponse, Error> {
     info!("Connection from: {}", req.peer_addr().expect("oops missing addr?"));
     //info!("Headers: {:?}", req.headers());
 
     let session = client_session::ClientSession::new(
         server.get_ref().clone(),
-        req.peer_addr().expect("oops")
+        req.peer_addr().expect("oops"),
+        query.encoding,
     );
 
     let resp = ws::WsResponseBuilder::new(session, &req, stream)
         // This will overwrite the codec's max frame-size
-        .frame_size(MAX_FRAME_SIZE)
+        .frame_size(config.max_frame_size)
         .start();
 
 
@@ -40,16 +50,50 @@ async fn main() -> std::io::Result<()> {
 
     env_logger::init();
 
-    let server = server::GameServer::new().start();
+    let config = match ServerConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    info!("Config: {:?}", config);
+
+    let history: Arc<dyn GameHistoryStorage> = Arc::new(
+        JsonLinesStorage::new(&config.history_dir).expect("history directory should be writable"));
+    let decks = match DeckRepository::load(&config.game.decks_dir) {
+        Ok(decks) => Arc::new(decks),
+        Err(errors) => {
+            for e in errors {
+                error!("{}", e);
+            }
+            std::process::exit(1);
+        }
+    };
+    // Keeps reloading decks until the server stops
+    let _deck_watcher = decks.watch().expect("decks directory should be watchable");
+    let server = server::GameServer::new(history.clone(), decks.clone(), &config).start();
+    let bind_address = (config.bind_address.clone(), config.port);
+    let config = web::Data::new(config);
 
     info!("init server");
     HttpServer::new(move ||
             App::new()
                 .app_data(web::Data::new(server.clone()))
+                .app_data(web::Data::from(history.clone()))
+                .app_data(config.clone())
+                .app_data(web::Data::from(decks.clone()))
                 .route("/ws/", web::get().to(ws_route))
+                .route("/decks", web::get().to(routes::decks::get_decks))
+                .route("/rooms", web::get().to(routes::rooms::get_rooms))
+                .route("/games/{id}", web::get().to(routes::games::get_game))
+                .route("/games/{id}/rounds/{n}", web::get().to(routes::games::get_round))
+                .route("/games/{id}/rounds/{n}/drawings/{drawing_id}.{format}",
+                    web::get().to(routes::games::get_drawing_image))
+                .route("/games/{id}/highest_rated.{format}",
+                    web::get().to(routes::games::get_highest_rated_image))
         )
-        // TODO: bind via env var
-        .bind(("127.0.0.1", 8080))?
+        .bind(bind_address)?
         .run()
         .await
 }
\ No newline at end of file
@@ -0,0 +1,82 @@
+pub mod svg;
+pub mod png;
+
+use crate::game::{color::Color, drawing::{Drawing, Stroke}};
+
+// Space around the strokes, in drawing coordinates
+const PADDING: f32 = 8.0;
+// Used when there's nothing to draw
+const EMPTY_SIZE: f32 = 100.0;
+
+#[derive(Debug)]
+pub enum RenderError {
+    InvalidSize,
+    Encoding(String),
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::InvalidSize => write!(f, "invalid image size"),
+            RenderError::Encoding(e) => write!(f, "failed to encode image: {}", e),
+        }
+    }
+}
+
+/// The region of drawing coordinates that ends up in the image
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    /***
+     * Smallest region that fits every stroke of every layer, including
+     * the width of the brush
+     */
+    pub fn fit(layers: &[&Drawing]) -> Viewport {
+        let mut bounds: Option<(f32, f32, f32, f32)> = None;
+        for stroke in layers.iter().flat_map(|d| d.iter()) {
+            let radius = stroke.brush_size as f32 / 2.0;
+            for (x, y) in finite_points(stroke) {
+                let (min_x, min_y, max_x, max_y) = bounds.unwrap_or((x, y, x, y));
+                bounds = Some((
+                    min_x.min(x - radius), min_y.min(y - radius),
+                    max_x.max(x + radius), max_y.max(y + radius)));
+            }
+        }
+        match bounds {
+            Some((min_x, min_y, max_x, max_y)) => Viewport {
+                min_x: min_x - PADDING,
+                min_y: min_y - PADDING,
+                width: max_x - min_x + 2.0 * PADDING,
+                height: max_y - min_y + 2.0 * PADDING,
+            },
+            None => Viewport { min_x: 0.0, min_y: 0.0, width: EMPTY_SIZE, height: EMPTY_SIZE },
+        }
+    }
+}
+
+/***
+ * The layers that make up what a player saw when they finished drawing:
+ * the imprint they were given, with their own strokes on top
+ */
+pub fn layers<'a>(drawing: &'a Drawing, imprint: Option<&'a Drawing>) -> Vec<&'a Drawing> {
+    imprint.into_iter().chain(std::iter::once(drawing)).collect()
+}
+
+/***
+ * Colors that can't be parsed are drawn in black rather than passed through
+ */
+fn stroke_color(stroke: &Stroke) -> Color {
+    Color::parse(&stroke.color).unwrap_or(Color::BLACK)
+}
+
+fn finite_points(stroke: &Stroke) -> impl Iterator<Item = (f32, f32)> + '_ {
+    stroke.coordinates.iter()
+        .copied()
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+}
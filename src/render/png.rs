@@ -0,0 +1,55 @@
+use tiny_skia::{LineCap, LineJoin, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::game::drawing::Drawing;
+
+use super::{finite_points, stroke_color, RenderError, Viewport};
+
+/***
+ * Rasterizes the layers, bottom first, to a PNG whose longest side is
+ * `size` pixels
+ */
+pub fn render(layers: &[&Drawing], size: u32) -> Result<Vec<u8>, RenderError> {
+    let viewport = Viewport::fit(layers);
+    let scale = size as f32 / viewport.width.max(viewport.height);
+    let width = (viewport.width * scale).round().max(1.0) as u32;
+    let height = (viewport.height * scale).round().max(1.0) as u32;
+
+    let mut pixmap = Pixmap::new(width, height).ok_or(RenderError::InvalidSize)?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+    let transform = Transform::from_row(
+        scale, 0.0, 0.0, scale, -viewport.min_x * scale, -viewport.min_y * scale);
+
+    for stroke in layers.iter().flat_map(|d| d.iter()) {
+        let color = stroke_color(stroke);
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(color.r, color.g, color.b, color.a);
+        paint.anti_alias = true;
+
+        let points: Vec<(f32, f32)> = finite_points(stroke).collect();
+        match points.as_slice() {
+            [] => (),
+            [(x, y)] => {
+                if let Some(dot) = PathBuilder::from_circle(*x, *y, stroke.brush_size as f32 / 2.0) {
+                    pixmap.fill_path(&dot, &paint, tiny_skia::FillRule::Winding, transform, None);
+                }
+            },
+            [(x, y), rest @ ..] => {
+                let mut pb = PathBuilder::new();
+                pb.move_to(*x, *y);
+                for (x, y) in rest {
+                    pb.line_to(*x, *y);
+                }
+                if let Some(path) = pb.finish() {
+                    let line = Stroke {
+                        width: stroke.brush_size as f32,
+                        line_cap: LineCap::Round,
+                        line_join: LineJoin::Round,
+                        ..Stroke::default()
+                    };
+                    pixmap.stroke_path(&path, &paint, &line, transform, None);
+                }
+            },
+        }
+    }
+    pixmap.encode_png().map_err(|e| RenderError::Encoding(e.to_string()))
+}
@@ -0,0 +1,41 @@
+use std::fmt::Write;
+
+use crate::game::drawing::Drawing;
+
+use super::{finite_points, stroke_color, Viewport};
+
+/***
+ * Renders the layers, bottom first, as an SVG document
+ */
+pub fn render(layers: &[&Drawing]) -> String {
+    let viewport = Viewport::fit(layers);
+    let mut svg = String::new();
+    write!(svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        viewport.min_x, viewport.min_y, viewport.width, viewport.height,
+        viewport.width.ceil(), viewport.height.ceil()).unwrap();
+    write!(svg,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="white"/>"#,
+        viewport.min_x, viewport.min_y, viewport.width, viewport.height).unwrap();
+
+    for stroke in layers.iter().flat_map(|d| d.iter()) {
+        let points: Vec<(f32, f32)> = finite_points(stroke).collect();
+        // Always written from the parsed color, never the client's string
+        let color = stroke_color(stroke).to_hex();
+        match points.as_slice() {
+            [] => (),
+            [(x, y)] => {
+                write!(svg, r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                    x, y, stroke.brush_size as f32 / 2.0, color).unwrap();
+            },
+            _ => {
+                let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+                write!(svg,
+                    r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                    points.join(" "), color, stroke.brush_size).unwrap();
+            },
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
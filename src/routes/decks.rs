@@ -0,0 +1,10 @@
+use actix_web::{web, HttpResponse, Responder};
+
+use crate::game::deck_repository::DeckRepository;
+
+/***
+ * GET /decks
+ */
+pub async fn get_decks(decks: web::Data<DeckRepository>) -> impl Responder {
+    HttpResponse::Ok().json(decks.list())
+}
@@ -0,0 +1,141 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::error;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::history::{game_record::{DrawingRecord, GameRecord}, storage::{GameHistoryStorage, StorageError}};
+use crate::render;
+
+const DEFAULT_IMAGE_SIZE: u32 = 512;
+const MAX_IMAGE_SIZE: u32 = 2048;
+
+#[derive(Deserialize, Debug)]
+pub struct ImageQuery {
+    // Draw the imprint the player was given underneath their drawing. Defaults to true.
+    imprint: Option<bool>,
+    // Longest side of a PNG, in pixels
+    size: Option<u32>,
+}
+
+async fn load_game(
+    history: web::Data<dyn GameHistoryStorage>,
+    game_id: Uuid,
+) -> Result<Option<GameRecord>, StorageError> {
+    let history = history.into_inner();
+    web::block(move || history.get(&game_id))
+        .await
+        .unwrap_or_else(|e| Err(StorageError::Io(std::io::Error::other(e))))
+}
+
+/***
+ * GET /games/{id}
+ */
+pub async fn get_game(
+    path: web::Path<Uuid>,
+    history: web::Data<dyn GameHistoryStorage>,
+) -> impl Responder {
+    match load_game(history, path.into_inner()).await {
+        Ok(Some(record)) => HttpResponse::Ok().json(record),
+        Ok(None) => HttpResponse::NotFound().body("game not found"),
+        Err(e) => {
+            error!("Failed to load game: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/***
+ * GET /games/{id}/rounds/{n}
+ */
+pub async fn get_round(
+    path: web::Path<(Uuid, usize)>,
+    history: web::Data<dyn GameHistoryStorage>,
+) -> impl Responder {
+    let (game_id, round) = path.into_inner();
+    match load_game(history, game_id).await {
+        Ok(Some(record)) => match record.get_round(round) {
+            Some(round_record) => HttpResponse::Ok().json(round_record),
+            None => HttpResponse::NotFound().body("round not found"),
+        },
+        Ok(None) => HttpResponse::NotFound().body("game not found"),
+        Err(e) => {
+            error!("Failed to load game: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/***
+ * GET /games/{id}/rounds/{n}/drawings/{drawing_id}.{svg|png}
+ */
+pub async fn get_drawing_image(
+    path: web::Path<(Uuid, usize, Uuid, String)>,
+    query: web::Query<ImageQuery>,
+    history: web::Data<dyn GameHistoryStorage>,
+) -> impl Responder {
+    let (game_id, round, drawing_id, format) = path.into_inner();
+    match load_game(history, game_id).await {
+        Ok(Some(record)) => {
+            match record.get_round(round).and_then(|r| r.get_drawing(&drawing_id)) {
+                Some(drawing) => render_image(drawing, &format, &query).await,
+                None => HttpResponse::NotFound().body("drawing not found"),
+            }
+        },
+        Ok(None) => HttpResponse::NotFound().body("game not found"),
+        Err(e) => {
+            error!("Failed to load game: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/***
+ * GET /games/{id}/highest_rated.{svg|png}
+ */
+pub async fn get_highest_rated_image(
+    path: web::Path<(Uuid, String)>,
+    query: web::Query<ImageQuery>,
+    history: web::Data<dyn GameHistoryStorage>,
+) -> impl Responder {
+    let (game_id, format) = path.into_inner();
+    match load_game(history, game_id).await {
+        Ok(Some(record)) => match record.get_highest_rated_drawing() {
+            Some(drawing) => render_image(drawing, &format, &query).await,
+            None => HttpResponse::NotFound().body("game has no drawings"),
+        },
+        Ok(None) => HttpResponse::NotFound().body("game not found"),
+        Err(e) => {
+            error!("Failed to load game: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+async fn render_image(record: &DrawingRecord, format: &str, query: &ImageQuery) -> HttpResponse {
+    let drawing = record.drawing.clone().unwrap_or_default();
+    let imprint = record.imprint.clone().filter(|_| query.imprint.unwrap_or(true));
+    match format {
+        "svg" => {
+            let svg = render::svg::render(&render::layers(&drawing, imprint.as_ref()));
+            HttpResponse::Ok().content_type("image/svg+xml").body(svg)
+        },
+        "png" => {
+            let size = query.size.unwrap_or(DEFAULT_IMAGE_SIZE).clamp(1, MAX_IMAGE_SIZE);
+            let png = web::block(move || {
+                render::png::render(&render::layers(&drawing, imprint.as_ref()), size)
+            }).await;
+            match png {
+                Ok(Ok(bytes)) => HttpResponse::Ok().content_type("image/png").body(bytes),
+                Ok(Err(e)) => {
+                    error!("Failed to render png: {}", e);
+                    HttpResponse::InternalServerError().finish()
+                },
+                Err(e) => {
+                    error!("Failed to render png: {:?}", e);
+                    HttpResponse::InternalServerError().finish()
+                }
+            }
+        },
+        _ => HttpResponse::NotFound().body("unsupported image format"),
+    }
+}
@@ -0,0 +1,3 @@
+pub mod decks;
+pub mod games;
+pub mod rooms;
@@ -0,0 +1,18 @@
+use actix::Addr;
+use actix_web::{web, HttpResponse, Responder};
+use log::error;
+
+use crate::websocket::server::{GameServer, ListPublicRoomsMessage};
+
+/***
+ * GET /rooms
+ */
+pub async fn get_rooms(server: web::Data<Addr<GameServer>>) -> impl Responder {
+    match server.send(ListPublicRoomsMessage).await {
+        Ok(rooms) => HttpResponse::Ok().json(rooms),
+        Err(e) => {
+            error!("Failed to list rooms: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -5,8 +5,11 @@ use actix_web_actors::ws;
 
 use log::{info, error};
 
-use serde_json::{Value};
+use serde::Serialize;
+use serde_json::Value;
 
+use crate::api::client_message::ClientMessage;
+use crate::api::response::{ApiResponse, MessageName, ResponseEnvelope};
 use crate::api::server_messages::*;
 use crate::server::{self, ClientRequestWrapper};
 use crate::websocket::server::ClientDisconnectMessage;
@@ -14,20 +17,23 @@ use crate::websocket::server::ClientDisconnectMessage;
 use uuid::Uuid;
 
 use super::client_connection::ClientConnection;
+use super::encoding::Encoding;
 
 pub struct ClientSession{
     id: Uuid,
     server: Addr<server::GameServer>,
     peer_addr: net::SocketAddr,
-
+    // Format for messages that carry drawings
+    encoding: Encoding,
 }
 
 impl ClientSession {
-    pub fn new(server: Addr<server::GameServer>, peer_addr: net::SocketAddr) -> Self {
+    pub fn new(server: Addr<server::GameServer>, peer_addr: net::SocketAddr, encoding: Encoding) -> Self {
         ClientSession {
             id: Uuid::new_v4(),
             server,
             peer_addr,
+            encoding,
         }
     }
 
@@ -38,16 +44,38 @@ impl ClientSession {
                 peer_addr: self.peer_addr,
                 actor_addr: ctx.address(),
             },
-            req: req,
+            req,
         }
     }
+
+    /***
+     * Sends a request to the GameServer and writes its response back
+     * to the client, tagged with the request's request_id
+     */
+    pub fn forward<T, R>(&self, req: T, request_id: Option<Value>, ctx: &mut ws::WebsocketContext<Self>)
+    where
+        T: Message<Result = ApiResponse<R>> + Send + 'static,
+        R: MessageName + Serialize + Send + 'static,
+        server::GameServer: Handler<ClientRequestWrapper<T>>,
+    {
+        self.server
+            .send(self.wrap_request(req, ctx))
+            .into_actor(self)
+            .then(|res, _, ctx| {
+                let resp = res.unwrap_or_else(|e| ApiResponse::ServerError(e.to_string()));
+                let envelope = ResponseEnvelope { request_id, response: &resp };
+                ctx.text(serde_json::to_string(&envelope).expect("should be JSON serializable"));
+                fut::ready(())
+            })
+            .wait(ctx);
+    }
 }
 
 impl Actor for ClientSession {
     type Context = ws::WebsocketContext<Self>;
 
     fn started(&mut self, _: &mut Self::Context) {
-        info!("New connection {} from {}", self.id, self.peer_addr);
+        info!("New connection {} from {} ({:?})", self.id, self.peer_addr, self.encoding);
     }
 
     fn stopped(&mut self, _: &mut Self::Context) {
@@ -67,120 +95,16 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ClientSession {
             Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
             Ok(ws::Message::Close(close_reason)) => {
                 info!("Connection closing: {:?}", close_reason);
-                ctx.close(close_reason)
+                ctx.close(close_reason);
+                ctx.stop();
             }
             Ok(ws::Message::Text(text)) => {
-                let json: Value = match serde_json::from_str(&text) {
-                    Ok(x) => x,
-                    Err(_) => {
-                        error!("Invalid JSON: {}", &text);
-                        ctx.stop();
-                        return;
+                match ClientMessage::parse(&text) {
+                    Ok((message, request_id)) => message.forward(request_id, self, ctx),
+                    Err(resp) => {
+                        error!("Invalid request from {}: {}", self.id, resp);
+                        ctx.text(resp);
                     }
-                };
-                match &json["message_name"] {
-                    Value::String(message_name) => {
-                        match message_name.as_str() {
-                            "create_game"  =>  {
-                                let req: crate::api::create_game::Request = serde_json::from_str(&text).expect("failed to parse");
-                                let l = self.server
-                                    .send(self.wrap_request(req, ctx))
-                                    .into_actor(self)
-                                    .then(|res, _, ctx|{
-                                        let js_resp = serde_json::to_string(&res.unwrap()).expect("oops");
-                                        ctx.text(js_resp);
-                                        fut::ready(())
-                                    });
-                                l.wait(ctx);
-                            }
-                            "join_game" => {
-                                let req: crate::api::join_game::Request = serde_json::from_str(&text).expect("failed to parse");
-                                let l = self.server
-                                    .send(self.wrap_request(req, ctx))
-                                    .into_actor(self)
-                                    .then(|res, _, ctx|{
-                                        let js_resp = serde_json::to_string(&res.unwrap()).expect("oops");
-                                        ctx.text(js_resp);
-                                        fut::ready(())
-                                    });
-                                l.wait(ctx);
-                            }
-                            "start_game" => {
-                                let req: crate::api::start_game::Request = serde_json::from_str(&text).expect("failed to parse");
-                                let l = self.server
-                                    .send(self.wrap_request(req, ctx))
-                                    .into_actor(self)
-                                    .then(|res, _, ctx|{
-                                        let js_resp = serde_json::to_string(&res.unwrap()).expect("oops");
-                                        ctx.text(js_resp);
-                                        fut::ready(())
-                                    });
-                                l.wait(ctx);
-                            }
-                            "set_player_ready" => {
-                                let req: crate::api::set_player_ready::Request = serde_json::from_str(&text).expect("failed to parse");
-                                let l = self.server
-                                    .send(self.wrap_request(req, ctx))
-                                    .into_actor(self)
-                                    .then(|res, _, ctx|{
-                                        let js_resp = serde_json::to_string(&res.unwrap()).expect("oops");
-                                        ctx.text(js_resp);
-                                        fut::ready(())
-                                    });
-                                l.wait(ctx);
-                            }
-                            "submit_drawing" => {
-                                let req: crate::api::submit_drawing::Request = serde_json::from_str(&text).expect("failed to parse");
-                                let l = self.server
-                                    .send(self.wrap_request(req, ctx))
-                                    .into_actor(self)
-                                    .then(|res, _, ctx|{
-                                        let js_resp = serde_json::to_string(&res.unwrap()).expect("oops");
-                                        ctx.text(js_resp);
-                                        fut::ready(())
-                                    });
-                                l.wait(ctx);
-                            }
-                            "submit_vote" => {
-                                let req: crate::api::submit_vote::Request = serde_json::from_str(&text).expect("failed to parse");
-                                let l = self.server
-                                    .send(self.wrap_request(req, ctx))
-                                    .into_actor(self)
-                                    .then(|res, _, ctx|{
-                                        let js_resp = serde_json::to_string(&res.unwrap()).expect("oops");
-                                        ctx.text(js_resp);
-                                        fut::ready(())
-                                    });
-                                l.wait(ctx);
-                            }
-                            "update_game_settings" => {
-                                let req: crate::api::update_game_settings::Request = serde_json::from_str(&text).expect("failed to parse");
-                                let l = self.server
-                                    .send(self.wrap_request(req, ctx))
-                                    .into_actor(self)
-                                    .then(|res, _, ctx|{
-                                        let js_resp = serde_json::to_string(&res.unwrap()).expect("oops");
-                                        ctx.text(js_resp);
-                                        fut::ready(())
-                                    });
-                                l.wait(ctx);
-                            }
-                            "play_again" => {
-                                let req: crate::api::play_again::Request = serde_json::from_str(&text).expect("failed to parse");
-                                let l = self.server
-                                    .send(self.wrap_request(req, ctx))
-                                    .into_actor(self)
-                                    .then(|res, _, ctx|{
-                                        let js_resp = serde_json::to_string(&res.unwrap()).expect("oops");
-                                        ctx.text(js_resp);
-                                        fut::ready(())
-                                    });
-                                l.wait(ctx);
-                            }
-                            _ => info!("unknown message {}", message_name)
-                        }
-                    },
-                    _ => info!("failure")
                 }
             }
             Err(e) => {
@@ -217,7 +141,19 @@ impl Handler<drawing_parameters::DrawingParameters> for ClientSession {
         msg: drawing_parameters::DrawingParameters,
         ctx: &mut Self::Context)
     -> Self::Result {
-        ctx.text(serde_json::to_string(&msg).expect("should be JSON serializable"));
+        ctx.write_raw(self.encoding.encode(&msg));
+    }
+}
+
+impl Handler<describing_parameters::DescribingParameters> for ClientSession {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        msg: describing_parameters::DescribingParameters,
+        ctx: &mut Self::Context)
+    -> Self::Result {
+        ctx.write_raw(self.encoding.encode(&msg));
     }
 }
 
@@ -229,7 +165,7 @@ impl Handler<voting_ballot::VotingBallot> for ClientSession {
         msg: voting_ballot::VotingBallot,
         ctx: &mut Self::Context)
     -> Self::Result {
-        ctx.text(serde_json::to_string(&msg).expect("should be JSON serializable"));
+        ctx.write_raw(self.encoding.encode(&msg));
     }
 }
 
@@ -245,6 +181,42 @@ impl Handler<game_settings_update::GameSettingsUpdate> for ClientSession {
     }
 }
 
+impl Handler<game_snapshot::GameSnapshot> for ClientSession {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        msg: game_snapshot::GameSnapshot,
+        ctx: &mut Self::Context)
+    -> Self::Result {
+        ctx.write_raw(self.encoding.encode(&msg));
+    }
+}
+
+impl Handler<player_removed::PlayerRemoved> for ClientSession {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        msg: player_removed::PlayerRemoved,
+        ctx: &mut Self::Context)
+    -> Self::Result {
+        ctx.text(serde_json::to_string(&msg).expect("should be JSON serializable"));
+    }
+}
+
+impl Handler<room_closed::RoomClosed> for ClientSession {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        msg: room_closed::RoomClosed,
+        ctx: &mut Self::Context)
+    -> Self::Result {
+        ctx.text(serde_json::to_string(&msg).expect("should be JSON serializable"));
+    }
+}
+
 impl Handler<results::Results> for ClientSession {
     type Result = ();
 
@@ -253,6 +225,6 @@ impl Handler<results::Results> for ClientSession {
         msg: results::Results,
         ctx: &mut Self::Context)
     -> Self::Result {
-        ctx.text(serde_json::to_string(&msg).expect("should be JSON serializable"));
+        ctx.write_raw(self.encoding.encode(&msg));
     }
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+use actix_web_actors::ws;
+use serde::{Deserialize, Serialize};
+
+/***
+ * Wire format for the messages that carry drawings. Chosen by the client
+ * when it connects, e.g. `/ws/?encoding=msgpack`. Requests and every other
+ * message are always JSON.
+ *
+ * With msgpack, those messages are sent as binary frames holding a
+ * MessagePack map per struct, keyed by the same field names as the JSON.
+ * Values are encoded the way JSON has them too: ids are UUID strings, so
+ * they can be sent back as-is in submit_vote and submit_guesses.
+ */
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Json,
+    // MessagePack with the same field names and id strings as the JSON messages
+    Msgpack,
+}
+
+/// Query string accepted by the websocket route
+#[derive(Deserialize, Debug)]
+pub struct ConnectQuery {
+    #[serde(default)]
+    pub encoding: Encoding,
+}
+
+impl Encoding {
+    /***
+     * Serializes a message into a websocket frame: text for JSON,
+     * binary for MessagePack
+     */
+    pub fn encode<T: Serialize>(self, msg: &T) -> ws::Message {
+        match self {
+            Encoding::Json => ws::Message::Text(
+                serde_json::to_string(msg).expect("should be JSON serializable").into()),
+            Encoding::Msgpack => {
+                let mut buf = Vec::new();
+                // Human readable, so that Uuids are strings rather than 16 raw bytes
+                let mut serializer = rmp_serde::Serializer::new(&mut buf)
+                    .with_struct_map()
+                    .with_human_readable();
+                msg.serialize(&mut serializer).expect("should be MessagePack serializable");
+                ws::Message::Binary(buf.into())
+            },
+        }
+    }
+}
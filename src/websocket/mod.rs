@@ -1,3 +1,4 @@
 pub mod client_session;
 pub mod server;
-pub mod client_connection;
\ No newline at end of file
+pub mod client_connection;
+pub mod encoding;
@@ -1,8 +1,9 @@
 use actix::prelude::*;
 use log::info;
-use std::{rc::Rc};
+use std::{rc::Rc, sync::Arc, time::Duration};
 
-use crate::{api::*, game::game_manager};
+use crate::{api::*, config::{CleanupConfig, ServerConfig}, game::{game_manager, game::RoomPrivacy, deck_repository::DeckRepository, room_code_generator::RoomCodeGenerator},
+    history::storage::GameHistoryStorage};
 
 use uuid::Uuid;
 
@@ -14,6 +15,11 @@ pub struct ClientDisconnectMessage{
     pub client_id: Uuid,
 }
 
+/// Asks for the public rooms, from outside of a websocket session
+#[derive(Message)]
+#[rtype(result = "Vec<list_public_rooms::RoomInfo>")]
+pub struct ListPublicRoomsMessage;
+
 #[derive(Debug)]
 pub struct ClientRequestWrapper<T: Message>{
     pub client_connection: ClientConnection,
@@ -26,19 +32,52 @@ impl<T: Message> Message for ClientRequestWrapper<T> {
 
 pub struct GameServer {
     gm: game_manager::GameManager,
+    cleanup: CleanupConfig,
 }
 
 
 impl GameServer {
-    pub fn new() -> Self {
+    pub fn new(history: Arc<dyn GameHistoryStorage>, decks: Arc<DeckRepository>, config: &ServerConfig) -> Self {
         GameServer {
-            gm: game_manager::GameManager::new(),
+            gm: game_manager::GameManager::new(
+                history,
+                decks,
+                RoomCodeGenerator::new(config.room_code_length, &config.room_code_alphabet, &config.room_code_blocklist),
+                config.game.clone()),
+            cleanup: config.cleanup.clone(),
+        }
+    }
+
+    /***
+     * Schedules the deadlines of any phases that started while handling
+     * the last message. Should be called after anything that can move a
+     * game into a new phase.
+     */
+    fn schedule_phase_timers(&mut self, ctx: &mut Context<Self>) {
+        for (room_code, timer) in self.gm.take_unscheduled_phase_timers() {
+            info!("Scheduling {:?} timer for round {} of room {} in {:?}",
+                timer.state, timer.round, room_code, timer.duration);
+            ctx.run_later(timer.duration, move |act, ctx| {
+                act.gm.expire_phase_timer(&room_code, &timer.id);
+                act.schedule_phase_timers(ctx);
+            });
         }
     }
 }
 
 impl Actor for GameServer {
     type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let interval = Duration::from_secs(self.cleanup.sweep_interval_seconds);
+        ctx.run_interval(interval, |act, ctx| {
+            act.gm.sweep(
+                Duration::from_secs(act.cleanup.idle_room_seconds),
+                Duration::from_secs(act.cleanup.disconnected_player_grace_seconds));
+            // Evicting players may end a phase
+            act.schedule_phase_timers(ctx);
+        });
+    }
 }
 
 impl Handler<ClientRequestWrapper<create_game::Request>> for GameServer {
@@ -49,9 +88,11 @@ impl Handler<ClientRequestWrapper<create_game::Request>> for GameServer {
         msg: ClientRequestWrapper<create_game::Request>,
         _ctx: &mut Context<Self>)
     -> Self::Result {
+        let privacy = RoomPrivacy { password: msg.req.password, is_public: msg.req.is_public };
         let res = self.gm.create_game(
             Rc::new(msg.client_connection),
-            msg.req.host_player_name);
+            msg.req.host_player_name,
+            privacy);
         MessageResult(response::ApiResponse::from(res))
     }
 }
@@ -65,7 +106,36 @@ impl Handler<ClientRequestWrapper<join_game::Request>> for GameServer {
         _ctx: &mut Context<Self>)
     -> Self::Result {
         let player_connection = Rc::new(msg.client_connection);
-        let res = self.gm.join_game(player_connection, &msg.req.room_code, &msg.req.player_name);
+        let res = self.gm.join_game(
+            player_connection, &msg.req.room_code, &msg.req.player_name, msg.req.password.as_deref());
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<rejoin_game::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<rejoin_game::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<rejoin_game::Request>,
+        _ctx: &mut Context<Self>)
+    -> Self::Result {
+        let player_connection = Rc::new(msg.client_connection);
+        let res = self.gm.rejoin_game(player_connection, &msg.req.room_code, &msg.req.reconnect_token);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<join_as_spectator::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<join_as_spectator::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<join_as_spectator::Request>,
+        _ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.join_as_spectator(
+            Rc::new(msg.client_connection), &msg.req.room_code, msg.req.password.as_deref());
         MessageResult(response::ApiResponse::from(res))
     }
 }
@@ -76,9 +146,10 @@ impl Handler<ClientRequestWrapper<start_game::Request>> for GameServer {
     fn handle(
         &mut self,
         msg: ClientRequestWrapper<start_game::Request>,
-        _ctx: &mut Context<Self>)
+        ctx: &mut Context<Self>)
     -> Self::Result {
         let res = self.gm.start_game(&msg.client_connection.id);
+        self.schedule_phase_timers(ctx);
         MessageResult(response::ApiResponse::from(res))
     }
 }
@@ -103,9 +174,10 @@ impl Handler<ClientRequestWrapper<submit_drawing::Request>> for GameServer {
     fn handle(
         &mut self,
         msg: ClientRequestWrapper<submit_drawing::Request>,
-        _ctx: &mut Context<Self>)
+        ctx: &mut Context<Self>)
     -> Self::Result {
         let res = self.gm.submit_drawing(&msg.client_connection.id, msg.req.drawing, msg.req.round);
+        self.schedule_phase_timers(ctx);
         MessageResult(response::ApiResponse::from(res))
     }
 }
@@ -116,9 +188,10 @@ impl Handler<ClientRequestWrapper<submit_vote::Request>> for GameServer {
     fn handle(
         &mut self,
         msg: ClientRequestWrapper<submit_vote::Request>,
-        _ctx: &mut Context<Self>)
+        ctx: &mut Context<Self>)
     -> Self::Result {
         let res = self.gm.submit_vote(&msg.client_connection.id, msg.req.votes);
+        self.schedule_phase_timers(ctx);
         MessageResult(response::ApiResponse::from(res))
     }
 }
@@ -149,16 +222,158 @@ impl Handler<ClientRequestWrapper<play_again::Request>> for GameServer {
     }
 }
 
+impl Handler<ClientRequestWrapper<submit_description::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<submit_description::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<submit_description::Request>,
+        ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.submit_description(&msg.client_connection.id, msg.req.round, &msg.req.description);
+        self.schedule_phase_timers(ctx);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<submit_guesses::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<submit_guesses::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<submit_guesses::Request>,
+        ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.submit_guesses(&msg.client_connection.id, msg.req.guesses);
+        self.schedule_phase_timers(ctx);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<choose_suggestion::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<choose_suggestion::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<choose_suggestion::Request>,
+        _ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.choose_suggestion(&msg.client_connection.id, msg.req.round, msg.req.choice);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<upload_custom_deck::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<upload_custom_deck::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<upload_custom_deck::Request>,
+        _ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.upload_custom_deck(&msg.client_connection.id, msg.req.deck_name, msg.req.words);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<assign_team::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<assign_team::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<assign_team::Request>,
+        _ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.assign_team(&msg.client_connection.id, &msg.req.player_name, msg.req.team);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<get_game_state::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<get_game_state::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<get_game_state::Request>,
+        _ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.get_game_state(&msg.client_connection.id);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<kick_player::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<kick_player::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<kick_player::Request>,
+        ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.kick_player(&msg.client_connection.id, &msg.req.player_name);
+        self.schedule_phase_timers(ctx);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<ban_player::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<ban_player::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<ban_player::Request>,
+        ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.ban_player(&msg.client_connection.id, &msg.req.player_name);
+        self.schedule_phase_timers(ctx);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<transfer_host::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<transfer_host::Request>>;
+
+    fn handle(
+        &mut self,
+        msg: ClientRequestWrapper<transfer_host::Request>,
+        _ctx: &mut Context<Self>)
+    -> Self::Result {
+        let res = self.gm.transfer_host(&msg.client_connection.id, &msg.req.player_name);
+        MessageResult(response::ApiResponse::from(res))
+    }
+}
+
+impl Handler<ClientRequestWrapper<list_public_rooms::Request>> for GameServer {
+    type Result = MessageResult<ClientRequestWrapper<list_public_rooms::Request>>;
+
+    fn handle(
+        &mut self,
+        _msg: ClientRequestWrapper<list_public_rooms::Request>,
+        _ctx: &mut Context<Self>)
+    -> Self::Result {
+        MessageResult(response::ApiResponse::from(self.gm.list_public_rooms()))
+    }
+}
+
+impl Handler<ListPublicRoomsMessage> for GameServer {
+    type Result = MessageResult<ListPublicRoomsMessage>;
+
+    fn handle(&mut self, _msg: ListPublicRoomsMessage, _ctx: &mut Context<Self>) -> Self::Result {
+        MessageResult(self.gm.list_public_rooms())
+    }
+}
+
 impl Handler<ClientDisconnectMessage> for GameServer {
     type Result = MessageResult<ClientDisconnectMessage>;
 
     fn handle(
         &mut self,
         msg: ClientDisconnectMessage,
-        _ctx: &mut Context<Self>)
+        ctx: &mut Context<Self>)
     -> Self::Result {
         info!("Received client disconnect: {}", msg.client_id);
         self.gm.remove_player_connection(&msg.client_id);
+        self.schedule_phase_timers(ctx);
         MessageResult(())
     }
 }
\ No newline at end of file